@@ -0,0 +1,119 @@
+pub mod random_agent;
+pub mod greedy_agent;
+pub mod ismcts_agent;
+
+pub use crate::agent::random_agent::RandomAgent;
+pub use crate::agent::greedy_agent::GreedyAgent;
+pub use crate::agent::ismcts_agent::{ IsmctsAgent, IsmctsConfig };
+
+use crate::board_state::{ PieceType, Player, PlayerView, StoredMove, ViewTile };
+use crate::game::Game;
+use crate::move_generation::MoveGeneration;
+
+// A bot that plays dark chess. it is only ever shown the fog of war view of its own player,
+// so it cannot read the hidden pieces off the BoardState the way a cheating bot could
+pub trait Agent {
+    // picks one of the legal moves, which is never empty when this is called
+    fn choose_move(&mut self, view: &PlayerView, legal: &[StoredMove]) -> StoredMove;
+}
+
+// the usual material values, with the king worth more than everything else together
+// since capturing it wins the game
+pub(crate) fn piece_value(piece_type: PieceType) -> u32 {
+    match piece_type {
+        PieceType::King => 100,
+        PieceType::Queen => 9,
+        PieceType::Rook => 5,
+        PieceType::Bishop => 3,
+        PieceType::Knight => 3,
+        PieceType::Pawn => 1,
+    }
+}
+
+// what the move captures, as far as the view shows. every tile a piece can move to is visible,
+// so the only capture not on the landing tile is en passant, a pawn moving diagonally onto an empty tile
+pub(crate) fn visible_capture(view: &PlayerView, stored_move: &StoredMove) -> Option<PieceType> {
+    let mover = view.get_tile_at_pos(stored_move.start_pos);
+    let target = view.get_tile_at_pos(stored_move.end_pos);
+
+    match (mover, target) {
+        (_, ViewTile::Visible(Some(piece))) => Some(*piece.get_piece_type()),
+        (ViewTile::Visible(Some(piece)), ViewTile::Visible(None))
+            if piece.get_piece_type() == &PieceType::Pawn
+            && stored_move.start_pos.get_file() != stored_move.end_pos.get_file() => Some(PieceType::Pawn),
+        _ => None,
+    }
+}
+
+// plays a game from game's current position between two agents, until it is over or max_plies
+// more moves have been played. panics if an agent picks a move it was not offered
+pub fn play_game(game: &mut Game, white: &mut dyn Agent, black: &mut dyn Agent, max_plies: usize) {
+    for _ in 0..max_plies {
+        let board_state = game.get_board_state();
+        let player = *board_state.get_player_turn();
+        let legal = MoveGeneration::gen_moves_for_player(board_state, player);
+        if legal.is_empty() {
+            return;
+        }
+
+        let view = PlayerView::new(board_state, player);
+        let agent: &mut dyn Agent = match player {
+            Player::White => &mut *white,
+            Player::Black => &mut *black,
+        };
+        let chosen = agent.choose_move(&view, &legal);
+        assert!(legal.contains(&chosen), "agent chose {}, which it was not offered", chosen);
+
+        game.try_move(player, chosen).unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board_state::GameStatus;
+
+    // always plays the first move offered
+    struct FirstMoveAgent {}
+
+    impl Agent for FirstMoveAgent {
+        fn choose_move(&mut self, _view: &PlayerView, legal: &[StoredMove]) -> StoredMove {
+            legal[0]
+        }
+    }
+
+    // tries to play a move that is never legal
+    struct CheatingAgent {}
+
+    impl Agent for CheatingAgent {
+        fn choose_move(&mut self, _view: &PlayerView, legal: &[StoredMove]) -> StoredMove {
+            StoredMove { start_pos: legal[0].end_pos, end_pos: legal[0].start_pos, promotion: None }
+        }
+    }
+
+    #[test]
+    fn play_game_stops_at_max_plies() {
+        let mut game = Game::new();
+
+        play_game(&mut game, &mut FirstMoveAgent {}, &mut FirstMoveAgent {}, 6);
+
+        assert_eq!(game.ply_count(), 6);
+        assert_eq!(game.get_result(), &GameStatus::Ongoing);
+    }
+
+    #[test]
+    fn play_game_stops_when_over() {
+        let mut game = Game::from_fen("4k3/8/8/8/8/8/8/4RK2 w - - 0 1").unwrap();
+
+        play_game(&mut game, &mut GreedyAgent::new(1), &mut FirstMoveAgent {}, 10);
+
+        assert_eq!(game.ply_count(), 1);
+        assert_eq!(game.get_result(), &GameStatus::WhiteWins);
+    }
+
+    #[test]
+    #[should_panic(expected = "which it was not offered")]
+    fn play_game_rejects_moves_not_offered() {
+        play_game(&mut Game::new(), &mut CheatingAgent {}, &mut FirstMoveAgent {}, 1);
+    }
+}
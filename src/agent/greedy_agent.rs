@@ -0,0 +1,105 @@
+use crate::agent::{ self, Agent };
+use crate::board_state::{ PlayerView, StoredMove };
+use crate::rng::Rng;
+
+// Captures the most valuable piece it can see, and otherwise plays a random move
+// ties between equally valuable captures are broken at random as well
+#[derive(Debug, Clone)]
+pub struct GreedyAgent {
+    rng: Rng,
+}
+
+impl GreedyAgent {
+    pub fn new(seed: u64) -> GreedyAgent {
+        GreedyAgent { rng: Rng::new(seed) }
+    }
+}
+
+impl Agent for GreedyAgent {
+    fn choose_move(&mut self, view: &PlayerView, legal: &[StoredMove]) -> StoredMove {
+        let best_value = legal.iter()
+            .map(|stored_move| agent::visible_capture(view, stored_move).map_or(0, agent::piece_value))
+            .max()
+            .expect("choose_move needs at least one legal move");
+
+        let best: Vec<StoredMove> = legal.iter()
+            .filter(|stored_move| agent::visible_capture(view, stored_move).map_or(0, agent::piece_value) == best_value)
+            .copied()
+            .collect();
+        *self.rng.choose(&best).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::{ play_game, RandomAgent };
+    use crate::board_state::{ BoardState, GameStatus, Player };
+    use crate::game::Game;
+    use crate::move_generation::MoveGeneration;
+
+    fn choose(fen: &str, seed: u64) -> StoredMove {
+        let board_state = BoardState::from_fen(fen).unwrap();
+        let player = *board_state.get_player_turn();
+        let view = PlayerView::new(&board_state, player);
+        let legal = MoveGeneration::gen_moves_for_player(&board_state, player);
+
+        GreedyAgent::new(seed).choose_move(&view, &legal)
+    }
+
+    #[test]
+    fn takes_the_queen_over_the_pawn() {
+        // the knight on d4 can take a pawn on c6 or the queen on e6
+        let fen = "4k3/8/2p1q3/8/3N4/8/8/4K3 w - - 0 1";
+
+        for seed in 0..10 {
+            assert_eq!(choose(fen, seed).to_uci(), "d4e6");
+        }
+    }
+
+    #[test]
+    fn takes_the_king_when_it_can() {
+        let fen = "4k3/8/8/8/8/8/3q4/4RK2 w - - 0 1";
+
+        for seed in 0..10 {
+            assert_eq!(choose(fen, seed).to_uci(), "e1e8");
+        }
+    }
+
+    #[test]
+    fn counts_en_passant_as_a_capture() {
+        let fen = "4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1";
+
+        for seed in 0..10 {
+            assert_eq!(choose(fen, seed).to_uci(), "e5d6");
+        }
+    }
+
+    #[test]
+    fn random_move_without_captures() {
+        let board_state = BoardState::new();
+        let view = PlayerView::new(&board_state, Player::White);
+        let legal = MoveGeneration::gen_moves_for_player(&board_state, Player::White);
+        let mut agent = GreedyAgent::new(8);
+
+        let mut chosen: Vec<String> = (0..200).map(|_| agent.choose_move(&view, &legal).to_uci()).collect();
+        chosen.sort();
+        chosen.dedup();
+
+        assert!(chosen.len() > 1);
+    }
+
+    #[test]
+    fn beats_random_agent_more_often_than_not() {
+        let mut greedy_wins = 0;
+        for seed in 0..20 {
+            let mut game = Game::new();
+            play_game(&mut game, &mut GreedyAgent::new(seed), &mut RandomAgent::new(seed + 100), 400);
+            if game.get_result() == &GameStatus::WhiteWins {
+                greedy_wins += 1;
+            }
+        }
+
+        assert!(greedy_wins > 10, "greedy won {} of 20", greedy_wins);
+    }
+}
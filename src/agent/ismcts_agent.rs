@@ -0,0 +1,489 @@
+use std::time::{ Duration, Instant };
+
+use crate::agent::{ self, Agent };
+use crate::board_state::{ Bitboards, BoardState, GameStatus, Piece, PieceType, Player, PlayerView, Square, StoredMove, ViewTile };
+use crate::board_state::bitboards::PIECE_TYPES;
+use crate::board_state::fen::{ placement_to_fen, player_to_fen };
+use crate::move_generation::MoveGeneration;
+use crate::rng::Rng;
+
+// How much searching IsmctsAgent does for each move, and how
+// the search stops at whichever budget runs out first, and runs one iteration if neither is set
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct IsmctsConfig {
+    pub iterations: Option<usize>,
+    pub time_limit: Option<Duration>,
+    // the UCT exploration constant. higher tries more moves, lower digs deeper into the best ones
+    pub exploration: f64,
+    // how many random moves a playout makes before the position is judged on material
+    pub rollout_depth: usize,
+    pub seed: u64,
+}
+
+impl IsmctsConfig {
+    pub fn new() -> IsmctsConfig {
+        IsmctsConfig {
+            iterations: Some(1000),
+            time_limit: None,
+            exploration: 0.7,
+            rollout_depth: 20,
+            seed: 0,
+        }
+    }
+}
+
+impl Default for IsmctsConfig {
+    fn default() -> Self {
+        IsmctsConfig::new()
+    }
+}
+
+// A node of the search tree, reached by playing stored_move from its parent
+// the tree is shared by every determinization, so a node only counts as available in
+// the iterations where its move was legal
+#[derive(Debug, Clone)]
+struct Node {
+    stored_move: Option<StoredMove>,
+    // the player who played stored_move, whose point of view reward is from
+    mover: Player,
+    children: Vec<usize>,
+    visits: u32,
+    reward: f64,
+    availability: u32,
+}
+
+impl Node {
+    fn new(stored_move: Option<StoredMove>, mover: Player) -> Node {
+        Node { stored_move, mover, children: Vec::new(), visits: 0, reward: 0.0, availability: 0 }
+    }
+}
+
+// Information set Monte Carlo tree search. since the agent cannot see the whole board, every
+// iteration samples a full board that agrees with everything it can see (a determinization), then
+// plays one UCT iteration on it. the statistics of all the iterations are kept in a single tree, so
+// the search settles on moves that are good across the boards the opponent could have
+// one agent should play one game, since it remembers what it has captured so far
+#[derive(Debug, Clone)]
+pub struct IsmctsAgent {
+    config: IsmctsConfig,
+    rng: Rng,
+    // the opponent pieces this agent has captured, which are no longer anywhere on the board
+    captured: Vec<PieceType>,
+}
+
+impl IsmctsAgent {
+    pub fn new(config: IsmctsConfig) -> IsmctsAgent {
+        IsmctsAgent {
+            config,
+            rng: Rng::new(config.seed),
+            captured: Vec::new(),
+        }
+    }
+
+    pub fn get_config(&self) -> &IsmctsConfig {
+        &self.config
+    }
+
+    fn budget_spent(&self, iterations: usize, start: Instant) -> bool {
+        let iterations_spent = self.config.iterations.is_some_and(|limit| iterations >= limit);
+        let time_spent = self.config.time_limit.is_some_and(|limit| start.elapsed() >= limit);
+        let no_budget = self.config.iterations.is_none() && self.config.time_limit.is_none();
+
+        iterations_spent || time_spent || (no_budget && iterations >= 1)
+    }
+
+    // a full board that matches the view. the hidden tiles get the opponent pieces that are neither
+    // visible nor captured, on random tiles, with pawns kept off the first and last ranks.
+    // castling rights and the en passant tile of the side to move are read off the legal moves,
+    // and the opponent is given no castling rights, since nothing seen can show them
+    fn determinize(&mut self, view: &PlayerView, legal: &[StoredMove]) -> BoardState {
+        let player = *view.get_player();
+        let opponent = player.opponent();
+
+        let mut tiles = [[ViewTile::Visible(None); 8]; 8];
+        let mut hidden = Vec::new();
+        let mut visible_opponent = Vec::new();
+        for pos in Square::all() {
+            match view.get_tile_at_pos(pos) {
+                ViewTile::Visible(piece) => {
+                    tiles[pos.get_rank()][pos.get_file()] = ViewTile::Visible(*piece);
+                    if let Some(piece) = piece.filter(|piece| piece.get_player() == &opponent) {
+                        visible_opponent.push(*piece.get_piece_type());
+                    }
+                },
+                ViewTile::Hidden => hidden.push(pos),
+            }
+        }
+
+        // the opponent starts with the pieces of the usual starting position
+        let start = BoardState::new();
+        let mut missing = Vec::new();
+        for piece_type in PIECE_TYPES {
+            let count = start.get_bitboards().get_pieces(Piece::new(piece_type, opponent)).count_ones() as usize;
+            let gone = visible_opponent.iter().chain(self.captured.iter())
+                .filter(|gone_type| **gone_type == piece_type)
+                .count();
+            missing.extend((0..count.saturating_sub(gone)).map(|_| piece_type));
+        }
+
+        // a hidden tile two steps ahead of a pawn that could not make its two step has to be blocked,
+        // and one diagonal to a pawn has to be empty, or the legal moves would be different
+        let (blocked, empty) = IsmctsAgent::pawn_constraints(view, legal);
+        hidden.retain(|pos| !blocked.contains(pos) && !empty.contains(pos));
+        for pos in blocked {
+            let piece_type = match missing.len() {
+                0 => PieceType::Pawn,
+                len => missing.swap_remove(self.rng.below(len)),
+            };
+            tiles[pos.get_rank()][pos.get_file()] = ViewTile::Visible(Some(Piece::new(piece_type, opponent)));
+        }
+
+        for piece_type in missing {
+            let allowed: Vec<usize> = (0..hidden.len())
+                .filter(|index| piece_type != PieceType::Pawn || (1..7).contains(&hidden[*index].get_rank()))
+                .collect();
+            if let Some(index) = self.rng.choose(&allowed) {
+                let pos = hidden.swap_remove(*index);
+                tiles[pos.get_rank()][pos.get_file()] = ViewTile::Visible(Some(Piece::new(piece_type, opponent)));
+            }
+        }
+
+        let fen = format!(
+            "{} {} {} {} 0 1",
+            placement_to_fen(&tiles),
+            player_to_fen(*view.get_player_turn()),
+            IsmctsAgent::castling_from_legal(view, legal),
+            IsmctsAgent::en_passant_from_legal(view, legal),
+        );
+        BoardState::from_fen(&fen).expect("a determinization is always a valid position")
+    }
+
+    // the hidden tiles that must hold a piece, and those that must be empty, going by the player's pawns
+    fn pawn_constraints(view: &PlayerView, legal: &[StoredMove]) -> (Vec<Square>, Vec<Square>) {
+        let player = *view.get_player();
+        let forward = player.forward();
+        let is_hidden = |pos: &Square| view.get_tile_at_pos(*pos) == &ViewTile::Hidden;
+
+        let mut blocked = Vec::new();
+        let mut empty = Vec::new();
+        for pos in Square::all() {
+            if view.get_tile_at_pos(pos) != &ViewTile::Visible(Some(Piece::new(PieceType::Pawn, player))) {
+                continue;
+            }
+
+            let front_empty = pos.offset(0, forward).is_some_and(|front| view.get_tile_at_pos(front) == &ViewTile::Visible(None));
+            if pos.get_rank() == player.pawn_rank() && front_empty {
+                let two_step = pos.offset(0, 2 * forward).filter(|pos| is_hidden(pos));
+                blocked.extend(two_step.filter(|end_pos| !legal.iter().any(|stored_move| stored_move.end_pos == *end_pos)));
+            }
+            empty.extend([pos.offset(1, forward), pos.offset(-1, forward)].iter().flatten().filter(|pos| is_hidden(pos)));
+        }
+
+        (blocked, empty)
+    }
+
+    // the castles among the legal moves, as a FEN castling field
+    fn castling_from_legal(view: &PlayerView, legal: &[StoredMove]) -> String {
+        let player = *view.get_player();
+        let mut castling: Vec<char> = legal.iter()
+            .filter(|stored_move| view.get_tile_at_pos(stored_move.start_pos)
+                == &ViewTile::Visible(Some(Piece::new(PieceType::King, player))))
+            .filter(|stored_move| stored_move.start_pos.get_file() == 4
+                && (stored_move.end_pos.get_file() as i32 - 4).abs() == 2)
+            .map(|stored_move| {
+                let symbol = if stored_move.end_pos.get_file() == 6 { 'K' } else { 'Q' };
+                match player {
+                    Player::White => symbol,
+                    Player::Black => symbol.to_ascii_lowercase(),
+                }
+            })
+            .collect();
+
+        if castling.is_empty() {
+            return String::from("-");
+        }
+        // FEN lists the kingside castle first
+        castling.sort_by_key(|symbol| !symbol.eq_ignore_ascii_case(&'K'));
+        castling.into_iter().collect()
+    }
+
+    // the tile of a legal en passant capture, as a FEN en passant field
+    fn en_passant_from_legal(view: &PlayerView, legal: &[StoredMove]) -> String {
+        legal.iter()
+            .find(|stored_move| view.get_tile_at_pos(stored_move.end_pos) == &ViewTile::Visible(None)
+                && agent::visible_capture(view, stored_move) == Some(PieceType::Pawn))
+            .map_or(String::from("-"), |stored_move| stored_move.end_pos.to_string())
+    }
+
+    fn search(&mut self, view: &PlayerView, legal: &[StoredMove]) -> StoredMove {
+        let player = *view.get_player();
+        let mut nodes = vec![Node::new(None, player.opponent())];
+        let start = Instant::now();
+
+        let mut iterations = 0;
+        while !self.budget_spent(iterations, start) {
+            let mut board_state = self.determinize(view, legal);
+            let path = self.select_and_expand(&mut nodes, &mut board_state);
+
+            let white_reward = self.rollout(&mut board_state);
+            for index in path {
+                let node = &mut nodes[index];
+                node.visits += 1;
+                node.reward += match node.mover {
+                    Player::White => white_reward,
+                    Player::Black => 1.0 - white_reward,
+                };
+            }
+            iterations += 1;
+        }
+
+        // the most visited move is the one the search trusts most
+        nodes[0].children.iter()
+            .filter_map(|child| nodes[*child].stored_move.map(|stored_move| (stored_move, nodes[*child].visits)))
+            .filter(|(stored_move, _)| legal.contains(stored_move))
+            .max_by_key(|(_, visits)| *visits)
+            .map(|(stored_move, _)| stored_move)
+            .unwrap_or_else(|| *self.rng.choose(legal).unwrap())
+    }
+
+    // walks down the tree with UCT through the moves legal on this board, until it reaches a node with
+    // a move not tried yet, which it adds. returns the nodes visited, starting from the root
+    fn select_and_expand(&mut self, nodes: &mut Vec<Node>, board_state: &mut BoardState) -> Vec<usize> {
+        let mut path = vec![0];
+        let mut current = 0;
+
+        while !board_state.get_game_status().is_over() {
+            let moves = MoveGeneration::gen_moves_for_player(board_state, *board_state.get_player_turn());
+            if moves.is_empty() {
+                break;
+            }
+
+            // every child that could be played on this board becomes available once more
+            let mut available = Vec::new();
+            for child in nodes[current].children.clone() {
+                if nodes[child].stored_move.is_some_and(|stored_move| moves.contains(&stored_move)) {
+                    nodes[child].availability += 1;
+                    available.push(child);
+                }
+            }
+
+            let untried: Vec<StoredMove> = moves.into_iter()
+                .filter(|stored_move| !available.iter().any(|child| nodes[*child].stored_move == Some(*stored_move)))
+                .collect();
+
+            if let Some(stored_move) = self.rng.choose(&untried).copied() {
+                let mut node = Node::new(Some(stored_move), *board_state.get_player_turn());
+                node.availability = 1;
+                nodes.push(node);
+
+                let child = nodes.len() - 1;
+                nodes[current].children.push(child);
+                board_state.move_piece(stored_move);
+                path.push(child);
+                break;
+            }
+
+            let exploration = self.config.exploration;
+            let ucb = |node: &Node| {
+                node.reward / node.visits as f64
+                    + exploration * ((node.availability as f64).ln() / node.visits as f64).sqrt()
+            };
+            current = *available.iter()
+                .max_by(|lhs, rhs| ucb(&nodes[**lhs]).total_cmp(&ucb(&nodes[**rhs])))
+                .unwrap();
+
+            board_state.move_piece(nodes[current].stored_move.unwrap());
+            path.push(current);
+        }
+
+        path
+    }
+
+    // plays random moves, except that a king is always taken when it can be, then scores the board
+    // from white's point of view: 1 for a win, 0 for a loss, and otherwise between the two by material
+    fn rollout(&mut self, board_state: &mut BoardState) -> f64 {
+        for _ in 0..self.config.rollout_depth {
+            if board_state.get_game_status().is_over() {
+                break;
+            }
+
+            let player = *board_state.get_player_turn();
+            let moves = MoveGeneration::gen_moves_for_player(board_state, player);
+            let enemy_king = board_state.get_bitboards()
+                .get_pieces(Piece::new(PieceType::King, player.opponent()));
+
+            let king_capture = moves.iter().find(|stored_move| enemy_king & Bitboards::bit(stored_move.end_pos) != 0);
+            match king_capture.or_else(|| self.rng.choose(&moves)) {
+                Some(stored_move) => board_state.move_piece(*stored_move),
+                None => break,
+            }
+        }
+
+        match board_state.get_game_status() {
+            GameStatus::WhiteWins => 1.0,
+            GameStatus::BlackWins => 0.0,
+            GameStatus::Draw(_) => 0.5,
+            GameStatus::Ongoing => IsmctsAgent::material_score(board_state),
+        }
+    }
+
+    // 0.5 for equal material, leaning towards whoever has more, but never as far as a win or loss
+    fn material_score(board_state: &BoardState) -> f64 {
+        let mut balance = 0.0;
+        for pos in Square::all() {
+            if let Some(piece) = board_state.get_tile_at_pos(pos).get_piece() {
+                let value = agent::piece_value(*piece.get_piece_type()) as f64;
+                balance += match piece.get_player() {
+                    Player::White => value,
+                    Player::Black => -value,
+                };
+            }
+        }
+
+        0.5 + (balance / 80.0).clamp(-0.45, 0.45)
+    }
+}
+
+impl Agent for IsmctsAgent {
+    fn choose_move(&mut self, view: &PlayerView, legal: &[StoredMove]) -> StoredMove {
+        let chosen = match legal {
+            [only] => *only,
+            _ => self.search(view, legal),
+        };
+
+        if let Some(piece_type) = agent::visible_capture(view, &chosen) {
+            self.captured.push(piece_type);
+        }
+        chosen
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::{ play_game, RandomAgent };
+    use crate::game::Game;
+
+    fn config(iterations: usize, seed: u64) -> IsmctsConfig {
+        IsmctsConfig { iterations: Some(iterations), seed, ..IsmctsConfig::new() }
+    }
+
+    fn view_and_legal(board_state: &BoardState) -> (PlayerView, Vec<StoredMove>) {
+        let player = *board_state.get_player_turn();
+        (PlayerView::new(board_state, player), MoveGeneration::gen_moves_for_player(board_state, player))
+    }
+
+    #[test]
+    fn determinization_matches_the_view() {
+        let board_state = BoardState::from_fen("r3k2r/pp3ppp/2n5/3pP3/8/2N5/PPP2PPP/R3K2R w KQkq d6 0 12").unwrap();
+        let (view, legal) = view_and_legal(&board_state);
+        let mut agent = IsmctsAgent::new(config(1, 5));
+
+        for _ in 0..50 {
+            let determinized = agent.determinize(&view, &legal);
+
+            for pos in Square::all() {
+                if let ViewTile::Visible(piece) = view.get_tile_at_pos(pos) {
+                    assert_eq!(determinized.get_tile_at_pos(pos).get_piece(), piece, "{}", pos);
+                }
+            }
+            let black_pieces = determinized.get_bitboards().get_player_pieces(Player::Black).count_ones();
+            assert_eq!(black_pieces, 16);
+            assert_eq!(MoveGeneration::gen_moves_for_player(&determinized, Player::White), legal);
+        }
+    }
+
+    #[test]
+    fn determinization_keeps_the_legal_moves_in_random_games() {
+        let mut rng = Rng::new(17);
+
+        for seed in 0..5 {
+            let mut board_state = BoardState::new();
+            let mut agent = IsmctsAgent::new(config(1, seed));
+
+            for _ in 0..60 {
+                let (view, legal) = view_and_legal(&board_state);
+                if legal.is_empty() {
+                    break;
+                }
+
+                for _ in 0..3 {
+                    let determinized = agent.determinize(&view, &legal);
+                    assert_eq!(
+                        MoveGeneration::gen_moves_for_player(&determinized, *view.get_player()),
+                        legal,
+                        "in {}", board_state.to_fen()
+                    );
+                }
+                board_state.move_piece(*rng.choose(&legal).unwrap());
+            }
+        }
+    }
+
+    #[test]
+    fn determinization_leaves_out_captured_pieces() {
+        let board_state = BoardState::new();
+        let (view, legal) = view_and_legal(&board_state);
+        let mut agent = IsmctsAgent::new(config(1, 5));
+        agent.captured = vec![PieceType::Queen, PieceType::Pawn];
+
+        let determinized = agent.determinize(&view, &legal);
+
+        assert_eq!(determinized.get_bitboards().get_pieces(Piece::new(PieceType::Queen, Player::Black)), 0);
+        assert_eq!(determinized.get_bitboards().get_player_pieces(Player::Black).count_ones(), 14);
+    }
+
+    #[test]
+    fn castling_read_off_legal_moves() {
+        let board_state = BoardState::from_fen("r3k2r/8/8/8/8/8/8/R3K2R b Qk - 0 1").unwrap();
+        let (view, legal) = view_and_legal(&board_state);
+
+        assert_eq!(IsmctsAgent::castling_from_legal(&view, &legal), "k");
+    }
+
+    #[test]
+    fn same_seed_same_move() {
+        let board_state = BoardState::from_fen("4k3/8/2p1q3/8/3N4/8/8/4K3 w - - 0 1").unwrap();
+        let (view, legal) = view_and_legal(&board_state);
+
+        let first = IsmctsAgent::new(config(200, 11)).choose_move(&view, &legal);
+        let second = IsmctsAgent::new(config(200, 11)).choose_move(&view, &legal);
+
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn takes_a_visible_king() {
+        let board_state = BoardState::from_fen("4k3/8/8/8/8/8/3q4/4RK2 w - - 0 1").unwrap();
+        let (view, legal) = view_and_legal(&board_state);
+
+        assert_eq!(IsmctsAgent::new(config(300, 2)).choose_move(&view, &legal).to_uci(), "e1e8");
+    }
+
+    #[test]
+    fn time_budget_stops_the_search() {
+        let board_state = BoardState::new();
+        let (view, legal) = view_and_legal(&board_state);
+        let time_config = IsmctsConfig { iterations: None, time_limit: Some(Duration::from_millis(50)), ..IsmctsConfig::new() };
+
+        let start = Instant::now();
+        let chosen = IsmctsAgent::new(time_config).choose_move(&view, &legal);
+
+        assert!(legal.contains(&chosen));
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn beats_random_agent() {
+        let mut ismcts_wins = 0;
+        for seed in 0..4 {
+            let mut game = Game::new();
+            play_game(&mut game, &mut IsmctsAgent::new(config(60, seed)), &mut RandomAgent::new(seed), 200);
+            if game.get_result() == &GameStatus::WhiteWins {
+                ismcts_wins += 1;
+            }
+        }
+
+        assert!(ismcts_wins >= 3, "ismcts won {} of 4", ismcts_wins);
+    }
+}
@@ -0,0 +1,56 @@
+use crate::agent::Agent;
+use crate::board_state::{ PlayerView, StoredMove };
+use crate::rng::Rng;
+
+// Picks uniformly at random from the legal moves, ignoring the view entirely
+// the weakest possible baseline, and the same seed always plays the same moves
+#[derive(Debug, Clone)]
+pub struct RandomAgent {
+    rng: Rng,
+}
+
+impl RandomAgent {
+    pub fn new(seed: u64) -> RandomAgent {
+        RandomAgent { rng: Rng::new(seed) }
+    }
+}
+
+impl Agent for RandomAgent {
+    fn choose_move(&mut self, _view: &PlayerView, legal: &[StoredMove]) -> StoredMove {
+        *self.rng.choose(legal).expect("choose_move needs at least one legal move")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::play_game;
+    use crate::board_state::BoardState;
+    use crate::game::Game;
+    use crate::move_generation::MoveGeneration;
+
+    #[test]
+    fn same_seed_same_game() {
+        let mut first = Game::new();
+        let mut second = Game::new();
+
+        play_game(&mut first, &mut RandomAgent::new(9), &mut RandomAgent::new(10), 40);
+        play_game(&mut second, &mut RandomAgent::new(9), &mut RandomAgent::new(10), 40);
+
+        assert_eq!(first.get_board_state(), second.get_board_state());
+    }
+
+    #[test]
+    fn picks_every_move_eventually() {
+        let board_state = BoardState::new();
+        let view = PlayerView::new(&board_state, *board_state.get_player_turn());
+        let legal = MoveGeneration::gen_moves_for_player(&board_state, *board_state.get_player_turn());
+        let mut agent = RandomAgent::new(4);
+
+        let mut chosen: Vec<StoredMove> = (0..1000).map(|_| agent.choose_move(&view, &legal)).collect();
+        chosen.sort_by_key(|stored_move| stored_move.to_uci());
+        chosen.dedup();
+
+        assert_eq!(chosen.len(), legal.len());
+    }
+}
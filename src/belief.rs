@@ -0,0 +1,496 @@
+pub mod tracked_piece;
+pub mod observation;
+pub mod contradiction;
+
+pub use crate::belief::tracked_piece::TrackedPiece;
+pub use crate::belief::observation::Observation;
+pub use crate::belief::contradiction::Contradiction;
+
+use crate::board_state::{ Bitboards, BoardState, Piece, PieceType, Player, PlayerView, Square, ViewTile };
+use crate::board_state::bitboards::PIECE_TYPES;
+use crate::move_generation::MoveGeneration;
+
+// Follows one player's observations through a game and keeps, for every opponent piece, the set
+// of squares it could be on. the sets are kept apart from each other, so they can hold more than
+// is really possible but never less, unless the observations contradict each other
+// every square in a piece's set is taken to be equally likely
+#[derive(Debug, Clone)]
+pub struct BeliefTracker {
+    player: Player,
+    pieces: Vec<TrackedPiece>,
+    last_view: PlayerView,
+    // where our pawn landed if our last move was a two step, since it can then be taken en passant
+    two_step: Option<Square>,
+    ply: usize,
+    contradictions: Vec<Contradiction>,
+}
+
+impl BeliefTracker {
+    // starts from the usual starting position, where every piece is known
+    pub fn new(player: Player) -> BeliefTracker {
+        let board_state = BoardState::new();
+        let opponent = player.opponent();
+        let pieces = Bitboards::squares(board_state.get_bitboards().get_player_pieces(opponent))
+            .map(|pos| TrackedPiece::new(board_state.get_tile_at_pos(pos).get_piece().unwrap(), pos))
+            .collect();
+
+        BeliefTracker {
+            player,
+            pieces,
+            last_view: PlayerView::new(&board_state, player),
+            two_step: None,
+            ply: 0,
+            contradictions: Vec::new(),
+        }
+    }
+
+    pub fn get_player(&self) -> &Player {
+        &self.player
+    }
+
+    // every opponent piece from the starting position, including the captured ones
+    pub fn get_pieces(&self) -> &Vec<TrackedPiece> {
+        &self.pieces
+    }
+
+    pub fn get_contradictions(&self) -> &Vec<Contradiction> {
+        &self.contradictions
+    }
+
+    // updates the sets with what was seen after the next ply, whichever player made it
+    pub fn observe(&mut self, observation: &Observation) {
+        self.ply += 1;
+        let view = &observation.view;
+        let mover = view.get_player_turn().opponent();
+
+        if mover == self.player {
+            if let Some(pos) = observation.capture {
+                self.remove_captured(pos);
+            }
+            self.two_step = self.find_two_step(view);
+        } else {
+            match observation.capture {
+                Some(pos) => self.move_capturer(pos),
+                None => self.spread(),
+            }
+            self.two_step = None;
+        }
+
+        self.apply_view(view);
+        self.last_view = view.clone();
+    }
+
+    // the chance that some opponent piece is on pos
+    pub fn probability_at(&self, pos: Square) -> f64 {
+        1.0 - self.pieces.iter().fold(1.0, |empty, tracked| empty * (1.0 - tracked.probability_at(pos)))
+    }
+
+    // probability_at for every square, indexed [rank][file] like the board
+    pub fn probabilities(&self) -> [[f64; 8]; 8] {
+        let mut probabilities = [[0.0; 8]; 8];
+        for pos in Square::all() {
+            probabilities[pos.get_rank()][pos.get_file()] = self.probability_at(pos);
+        }
+        probabilities
+    }
+
+    fn live_indices(&self) -> Vec<usize> {
+        (0..self.pieces.len()).filter(|i| !self.pieces[*i].captured).collect()
+    }
+
+    fn own_pieces(&self, view: &PlayerView) -> u64 {
+        Square::all()
+            .filter(|pos| matches!(view.get_tile_at_pos(*pos), ViewTile::Visible(Some(piece)) if piece.get_player() == &self.player))
+            .fold(0, |bits, pos| bits | Bitboards::bit(pos))
+    }
+
+    // our move took an opponent piece off pos, which we must have been able to see
+    fn remove_captured(&mut self, pos: Square) {
+        let piece_type = match self.last_view.get_tile_at_pos(pos) {
+            ViewTile::Visible(Some(piece)) if piece.get_player() != &self.player => *piece.get_piece_type(),
+            _ => {
+                self.contradictions.push(Contradiction::UnexplainedCapture { ply: self.ply, pos });
+                return;
+            }
+        };
+
+        let bit = Bitboards::bit(pos);
+        let candidates: Vec<usize> = self.live_indices().into_iter()
+            .filter(|i| self.pieces[*i].could_be(piece_type) && self.pieces[*i].squares & bit != 0)
+            .collect();
+
+        // when more than one piece could have been taken, the survivors could be wherever any of
+        // them was. a pawn that may have promoted can stand in for any of the others, so it is kept
+        let removed = match candidates.iter().find(|i| !self.pieces[**i].may_have_promoted).or_else(|| candidates.first()) {
+            Some(i) => *i,
+            None => {
+                self.contradictions.push(Contradiction::UnexplainedCapture { ply: self.ply, pos });
+                return;
+            }
+        };
+        let survivor_squares = candidates.iter().fold(0, |bits, i| bits | self.pieces[*i].squares) & !bit;
+
+        for i in candidates {
+            self.pieces[i].squares = survivor_squares;
+        }
+        self.pieces[removed].squares = 0;
+        self.pieces[removed].captured = true;
+    }
+
+    // the tile our pawn landed on, if the move just made was a two step
+    fn find_two_step(&self, view: &PlayerView) -> Option<Square> {
+        let before = self.own_pieces(&self.last_view);
+        let after = self.own_pieces(view);
+        let (from, to) = (before & !after, after & !before);
+        if from.count_ones() != 1 || to.count_ones() != 1 {
+            return None;
+        }
+
+        let (from, to) = (Bitboards::squares(from).next()?, Bitboards::squares(to).next()?);
+        let is_pawn = matches!(view.get_tile_at_pos(to), ViewTile::Visible(Some(piece)) if piece.get_piece_type() == &PieceType::Pawn);
+        let two_step = from.offset(0, 2 * self.player.forward()) == Some(to);
+
+        if is_pawn && two_step { Some(to) } else { None }
+    }
+
+    // a board holding our pieces as they were last seen, and every opponent piece whose square is
+    // known. only one piece moves each ply, so while working out where one piece could have gone,
+    // every other piece with a single square is still on it and can block it
+    fn base_board(&self) -> BoardState {
+        let mut board_state = BoardState::empty();
+        for pos in Bitboards::squares(self.own_pieces(&self.last_view)) {
+            if let ViewTile::Visible(piece) = self.last_view.get_tile_at_pos(pos) {
+                board_state.set_piece_at_pos(*piece, pos);
+            }
+        }
+        for tracked in self.pieces.iter().filter(|tracked| !tracked.captured && tracked.squares.count_ones() == 1) {
+            board_state.set_piece_at_pos(Some(tracked.piece), Bitboards::squares(tracked.squares).next().unwrap());
+        }
+        board_state
+    }
+
+    // the squares the tracked piece could move to from any square in its set, kept to mask, and
+    // whether one of those moves could be a promotion. the base board has no castling rights,
+    // so castles are left to spread_castles
+    fn reach(&self, base: &mut BoardState, tracked: &TrackedPiece, mask: u64) -> (u64, bool) {
+        let opponent = *tracked.piece.get_player();
+        let mut targets = 0;
+        let mut promotes = false;
+
+        for pos in Bitboards::squares(tracked.squares) {
+            for piece_type in tracked.possible_types() {
+                base.set_piece_at_pos(Some(Piece::new(piece_type, opponent)), pos);
+                for stored_move in MoveGeneration::gen_moves_for_pos(base, pos) {
+                    let bit = Bitboards::bit(stored_move.end_pos);
+                    if bit & mask != 0 {
+                        targets |= bit;
+                        promotes |= stored_move.promotion.is_some();
+                    }
+                }
+            }
+            base.set_piece_at_pos(None, pos);
+        }
+
+        // a piece with a single square blocks the others, so it goes back once it is done with
+        if tracked.squares.count_ones() == 1 {
+            let pos = Bitboards::squares(tracked.squares).next().unwrap();
+            base.set_piece_at_pos(Some(tracked.piece), pos);
+        }
+
+        (targets, promotes)
+    }
+
+    // the opponent moved without capturing, so any one piece could have made any quiet move
+    fn spread(&mut self) {
+        let mut base = self.base_board();
+        let quiet = !self.own_pieces(&self.last_view);
+
+        for i in self.live_indices() {
+            let (targets, promotes) = self.reach(&mut base, &self.pieces[i], quiet);
+            self.pieces[i].squares |= targets;
+            self.pieces[i].may_have_promoted |= promotes;
+        }
+
+        self.spread_castles(quiet);
+    }
+
+    // a castle moves both the king and a rook, so it is added to both sets by hand. this only
+    // asks that each could still be on its starting square, which is enough to never miss one
+    fn spread_castles(&mut self, quiet: u64) {
+        let opponent = self.player.opponent();
+        let on_rank = |file| Bitboards::bit(Square::new(file, opponent.back_rank()).unwrap());
+
+        let live = self.live_indices();
+        let king = live.iter().copied()
+            .find(|i| self.pieces[*i].piece.get_piece_type() == &PieceType::King && self.pieces[*i].squares & on_rank(4) != 0);
+        let king = match king {
+            Some(king) => king,
+            None => return,
+        };
+
+        // (rook file, rook destination file, king destination file)
+        for (rook_file, rook_end, king_end) in [(7, 5, 6), (0, 3, 2)].iter() {
+            // either rook could be back in the corner, so every rook that might be there can castle
+            let rooks: Vec<usize> = live.iter().copied()
+                .filter(|i| self.pieces[*i].piece.get_piece_type() == &PieceType::Rook && self.pieces[*i].squares & on_rank(*rook_file) != 0)
+                .collect();
+
+            if !rooks.is_empty() && (on_rank(*king_end) | on_rank(*rook_end)) & !quiet == 0 {
+                self.pieces[king].squares |= on_rank(*king_end);
+                for rook in rooks {
+                    self.pieces[rook].squares |= on_rank(*rook_end);
+                }
+            }
+        }
+    }
+
+    // the opponent took one of our pieces on pos, so only the piece that took it has moved
+    fn move_capturer(&mut self, pos: Square) {
+        let mut base = self.base_board();
+        let bit = Bitboards::bit(pos);
+        // our pawn that just made a two step can also be taken en passant, from beside it
+        let en_passant = self.two_step.filter(|two_step| two_step == &pos).and_then(|_| pos.offset(0, -self.player.forward()));
+
+        let mut landings = Vec::new();
+        for i in self.live_indices() {
+            let (mut targets, promotes) = self.reach(&mut base, &self.pieces[i], bit);
+
+            if let Some(en_passant) = en_passant {
+                let tracked = &self.pieces[i];
+                let beside = [pos.offset(-1, 0), pos.offset(1, 0)].iter()
+                    .flatten()
+                    .fold(0, |bits, beside| bits | Bitboards::bit(*beside));
+                if tracked.could_be(PieceType::Pawn) && tracked.squares & beside != 0 {
+                    targets |= Bitboards::bit(en_passant);
+                }
+            }
+
+            if targets != 0 {
+                landings.push((i, targets, promotes));
+            }
+        }
+
+        match landings.len() {
+            0 => self.contradictions.push(Contradiction::UnexplainedCapture { ply: self.ply, pos }),
+            // the only piece that could have made the capture is now on its landing square
+            1 => {
+                let (i, targets, promotes) = landings[0];
+                self.pieces[i].squares = targets;
+                self.pieces[i].may_have_promoted |= promotes;
+            },
+            _ => for (i, targets, promotes) in landings {
+                self.pieces[i].squares |= targets;
+                self.pieces[i].may_have_promoted |= promotes;
+            },
+        }
+    }
+
+    // narrows every set down with what the view shows. nothing can be on a visible empty tile or
+    // on one of our pieces, and a visible opponent piece has to be one of the pieces that could be there
+    fn apply_view(&mut self, view: &PlayerView) {
+        let mut hidden = 0;
+        let mut seen = [0; 6];
+        for pos in Square::all() {
+            let bit = Bitboards::bit(pos);
+            match view.get_tile_at_pos(pos) {
+                ViewTile::Hidden => hidden |= bit,
+                ViewTile::Visible(Some(piece)) if piece.get_player() != &self.player =>
+                    seen[Bitboards::piece_type_index(*piece.get_piece_type())] |= bit,
+                ViewTile::Visible(_) => (),
+            }
+        }
+        let seen_any = seen.iter().fold(0, |bits, seen| bits | seen);
+
+        let live = self.live_indices();
+        for i in live.iter() {
+            self.pieces[*i].squares &= hidden | seen_any;
+        }
+
+        for piece_type in PIECE_TYPES.iter() {
+            let seen = seen[Bitboards::piece_type_index(*piece_type)];
+            if seen == 0 {
+                continue;
+            }
+
+            let mut candidates = Vec::new();
+            for i in live.iter() {
+                if !self.pieces[*i].could_be(*piece_type) {
+                    self.pieces[*i].squares &= !seen;
+                } else if self.pieces[*i].squares & seen != 0 {
+                    candidates.push(*i);
+                }
+            }
+
+            let covered = candidates.iter().fold(0, |bits, i| bits | self.pieces[*i].squares) & seen;
+            for pos in Bitboards::squares(seen & !covered) {
+                self.contradictions.push(Contradiction::UnexplainedPiece { ply: self.ply, pos });
+            }
+            if covered == seen && candidates.len() < seen.count_ones() as usize {
+                let pos = Bitboards::squares(seen).next().unwrap();
+                self.contradictions.push(Contradiction::UnexplainedPiece { ply: self.ply, pos });
+            }
+
+            // with no more candidates than pieces seen, every candidate has to be one of them
+            if candidates.len() <= seen.count_ones() as usize {
+                for i in candidates {
+                    self.pieces[i].squares &= seen;
+                }
+            }
+        }
+
+        for i in live {
+            if self.pieces[i].squares == 0 {
+                let start = self.pieces[i].start;
+                self.contradictions.push(Contradiction::NoSquaresLeft { ply: self.ply, start });
+                // carry on as if it could be anywhere hidden, so one bad observation does not end the tracking
+                self.pieces[i].squares = hidden;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board_state::square::sq;
+    use crate::board_state::StoredMove;
+    use crate::rng::Rng;
+
+    // plays the move on board_state and shows it to both trackers
+    fn play(board_state: &mut BoardState, trackers: &mut [BeliefTracker], stored_move: StoredMove) {
+        let record = board_state.try_move(*board_state.get_player_turn(), stored_move).unwrap();
+        for tracker in trackers.iter_mut() {
+            let player = *tracker.get_player();
+            tracker.observe(&Observation::after_move(board_state, player, &record));
+        }
+    }
+
+    fn play_uci(board_state: &mut BoardState, trackers: &mut [BeliefTracker], moves: &[&str]) {
+        for uci in moves {
+            let stored_move = StoredMove::from_uci(uci).unwrap();
+            play(board_state, trackers, stored_move);
+        }
+    }
+
+    fn tracked_from<'a>(tracker: &'a BeliefTracker, start: &str) -> &'a TrackedPiece {
+        tracker.get_pieces().iter().find(|tracked| tracked.get_start() == &sq(start)).unwrap()
+    }
+
+    #[test]
+    fn start_position_is_known() {
+        let tracker = BeliefTracker::new(Player::White);
+
+        assert_eq!(tracker.get_pieces().len(), 16);
+        assert_eq!(tracker.probability_at(sq("e8")), 1.0);
+        assert_eq!(tracker.probability_at(sq("e7")), 1.0);
+        assert_eq!(tracker.probability_at(sq("e5")), 0.0);
+        assert!(tracker.get_pieces().iter().all(|tracked| tracked.get_piece().get_player() == &Player::Black));
+    }
+
+    #[test]
+    fn hidden_knight_move_spreads_out() {
+        let mut board_state = BoardState::new();
+        let mut trackers = [BeliefTracker::new(Player::White)];
+
+        play_uci(&mut board_state, &mut trackers, &["a2a3", "g8f6"]);
+
+        let knight = tracked_from(&trackers[0], "g8");
+        assert_eq!(knight.get_squares(), vec![sq("f6"), sq("h6"), sq("g8")]);
+        assert!((knight.probability_at(sq("f6")) - 1.0 / 3.0).abs() < 1e-9);
+        assert!(trackers[0].get_contradictions().is_empty());
+    }
+
+    #[test]
+    fn seen_piece_pins_its_set() {
+        let mut board_state = BoardState::new();
+        let mut trackers = [BeliefTracker::new(Player::White)];
+
+        // the pawn on e4 sees e5 but not e6, so black's pawn is known to have made the two step
+        play_uci(&mut board_state, &mut trackers, &["e2e4", "e7e5"]);
+
+        assert_eq!(tracked_from(&trackers[0], "e7").get_squares(), vec![sq("e5")]);
+        assert_eq!(trackers[0].probability_at(sq("e5")), 1.0);
+    }
+
+    #[test]
+    fn capture_moves_only_the_capturers() {
+        let mut board_state = BoardState::new();
+        let mut trackers = [BeliefTracker::new(Player::White)];
+
+        // the knight on c3 sees e4, and d5 is left empty, so the pawn that was on d5 made the capture
+        play_uci(&mut board_state, &mut trackers, &["e2e4", "d7d5", "b1c3", "d5e4"]);
+
+        let tracker = &trackers[0];
+        assert_eq!(tracked_from(tracker, "d7").get_squares(), vec![sq("e4")]);
+        // the f pawn could have taken from f5 as far as the sets go, but it could not have moved anywhere else
+        assert_eq!(tracked_from(tracker, "f7").get_squares(), vec![sq("e4"), sq("f5"), sq("f6"), sq("f7")]);
+        assert_eq!(tracker.probability_at(sq("e4")), 1.0);
+        assert!(tracker.get_contradictions().is_empty());
+    }
+
+    #[test]
+    fn our_capture_removes_the_piece() {
+        let mut board_state = BoardState::new();
+        let mut trackers = [BeliefTracker::new(Player::White)];
+
+        play_uci(&mut board_state, &mut trackers, &["e2e4", "d7d5", "e4d5"]);
+
+        let pawn = tracked_from(&trackers[0], "d7");
+        assert!(pawn.is_captured());
+        assert_eq!(pawn.probability_at(sq("d5")), 0.0);
+        assert_eq!(trackers[0].get_pieces().iter().filter(|tracked| !tracked.is_captured()).count(), 15);
+    }
+
+    #[test]
+    fn impossible_piece_is_a_contradiction() {
+        let board_state = BoardState::from_fen("rnbqkbnr/pppppppp/8/8/8/4q3/PPPPPPPP/RNBQKBNR b KQkq - 0 1").unwrap();
+        let mut tracker = BeliefTracker::new(Player::White);
+
+        tracker.observe(&Observation { view: PlayerView::new(&board_state, Player::White), capture: None });
+
+        assert_eq!(tracker.get_contradictions(), &vec![Contradiction::UnexplainedPiece { ply: 1, pos: sq("e3") }]);
+    }
+
+    #[test]
+    fn probabilities_match_probability_at() {
+        let mut board_state = BoardState::new();
+        let mut trackers = [BeliefTracker::new(Player::Black)];
+
+        play_uci(&mut board_state, &mut trackers, &["g1f3"]);
+
+        let probabilities = trackers[0].probabilities();
+        assert_eq!(probabilities[2][5], trackers[0].probability_at(sq("f3")));
+        assert!(probabilities[2][5] > 0.0 && probabilities[2][5] < 1.0);
+    }
+
+    #[test]
+    fn truth_always_possible_in_random_games() {
+        let mut rng = Rng::new(25);
+
+        for _ in 0..40 {
+            let mut board_state = BoardState::new();
+            let mut trackers = [BeliefTracker::new(Player::White), BeliefTracker::new(Player::Black)];
+
+            for _ in 0..150 {
+                let moves = MoveGeneration::gen_moves_for_player(&board_state, *board_state.get_player_turn());
+                let stored_move = match rng.choose(&moves) {
+                    Some(stored_move) => *stored_move,
+                    None => break,
+                };
+                play(&mut board_state, &mut trackers, stored_move);
+
+                for tracker in trackers.iter() {
+                    assert!(tracker.get_contradictions().is_empty(), "{:?}", tracker.get_contradictions());
+
+                    let opponent = tracker.get_player().opponent();
+                    let real = board_state.get_bitboards().get_player_pieces(opponent);
+                    let live = tracker.get_pieces().iter().filter(|tracked| !tracked.is_captured()).count();
+                    assert_eq!(live, real.count_ones() as usize);
+                    for pos in Bitboards::squares(real) {
+                        assert!(tracker.probability_at(pos) > 0.0, "{} after {}", pos, stored_move);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,41 @@
+use std::fmt;
+
+use crate::board_state::Square;
+
+// An observation the tracker could not explain, with the ply it came on (the first ply is 1)
+// these point to a bug or to observations that were not in the order they happened
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Contradiction {
+    // an opponent piece was seen on pos, but none of the tracked pieces could be there
+    UnexplainedPiece { ply: usize, pos: Square },
+    // a piece was captured on pos, but no opponent piece could have made the capture
+    UnexplainedCapture { ply: usize, pos: Square },
+    // the piece that started on start has nowhere left it could be
+    NoSquaresLeft { ply: usize, start: Square },
+}
+
+impl fmt::Display for Contradiction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Contradiction::UnexplainedPiece { ply, pos } =>
+                write!(f, "ply {}: no tracked piece could be the one seen on {}", ply, pos),
+            Contradiction::UnexplainedCapture { ply, pos } =>
+                write!(f, "ply {}: no tracked piece could have captured on {}", ply, pos),
+            Contradiction::NoSquaresLeft { ply, start } =>
+                write!(f, "ply {}: the piece that started on {} has nowhere left to be", ply, start),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board_state::square::sq;
+
+    #[test]
+    fn display_no_squares_left() {
+        let contradiction = Contradiction::NoSquaresLeft { ply: 3, start: sq("g8") };
+
+        assert_eq!(contradiction.to_string(), "ply 3: the piece that started on g8 has nowhere left to be");
+    }
+}
@@ -0,0 +1,39 @@
+use crate::board_state::{ BoardState, MoveRecord, Player, PlayerView, Square };
+
+// What one player learns from a single ply: the board as they now see it, and the tile a piece
+// was captured from, if there was a capture. a player always knows when one of their pieces is
+// taken, and where, as well as what they took themselves
+#[derive(Debug, Clone, PartialEq)]
+pub struct Observation {
+    pub view: PlayerView,
+    pub capture: Option<Square>,
+}
+
+impl Observation {
+    // what player learns from the move in record, given the board after it was played
+    pub fn after_move(board_state: &BoardState, player: Player, record: &MoveRecord) -> Observation {
+        Observation {
+            view: PlayerView::new(board_state, player),
+            capture: record.captured.map(|(_, pos)| pos),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board_state::square::sq;
+    use crate::board_state::StoredMove;
+
+    #[test]
+    fn after_move_keeps_capture_tile() {
+        let mut board_state = BoardState::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1").unwrap();
+        let stored_move = StoredMove { start_pos: sq("e5"), end_pos: sq("d6"), promotion: None };
+        let record = board_state.try_move(Player::White, stored_move).unwrap();
+
+        let observation = Observation::after_move(&board_state, Player::Black, &record);
+
+        assert_eq!(observation.capture, Some(sq("d5")));
+        assert_eq!(observation.view, PlayerView::new(&board_state, Player::Black));
+    }
+}
@@ -0,0 +1,109 @@
+use crate::board_state::{ Bitboards, Piece, PieceType, Square };
+
+// One of the opponent's pieces as far as the tracker knows, told apart from the others by the
+// square it started on. a pawn keeps its identity when it promotes, but from then on it may be
+// any piece a pawn can become
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TrackedPiece {
+    pub(crate) piece: Piece,
+    pub(crate) start: Square,
+    // every square the piece could be on, as a bitboard
+    pub(crate) squares: u64,
+    pub(crate) may_have_promoted: bool,
+    pub(crate) captured: bool,
+}
+
+impl TrackedPiece {
+    pub(crate) fn new(piece: Piece, start: Square) -> TrackedPiece {
+        TrackedPiece {
+            piece,
+            start,
+            squares: Bitboards::bit(start),
+            may_have_promoted: false,
+            captured: false,
+        }
+    }
+
+    // the piece as it started the game
+    pub fn get_piece(&self) -> &Piece {
+        &self.piece
+    }
+
+    pub fn get_start(&self) -> &Square {
+        &self.start
+    }
+
+    pub fn get_squares(&self) -> Vec<Square> {
+        Bitboards::squares(self.squares).collect()
+    }
+
+    pub fn may_have_promoted(&self) -> bool {
+        self.may_have_promoted
+    }
+
+    pub fn is_captured(&self) -> bool {
+        self.captured
+    }
+
+    // the chance the piece is on pos, taking each of its squares as equally likely
+    pub fn probability_at(&self, pos: Square) -> f64 {
+        if self.captured || self.squares & Bitboards::bit(pos) == 0 {
+            0.0
+        } else {
+            1.0 / self.squares.count_ones() as f64
+        }
+    }
+
+    // whether the piece could now be of piece_type, counting promotion
+    pub fn could_be(&self, piece_type: PieceType) -> bool {
+        let promoted_to = self.may_have_promoted && piece_type != PieceType::Pawn && piece_type != PieceType::King;
+
+        self.piece.get_piece_type() == &piece_type || promoted_to
+    }
+
+    // the types the piece could now be, whose moves it might make
+    pub(crate) fn possible_types(&self) -> Vec<PieceType> {
+        let mut types = vec![*self.piece.get_piece_type()];
+        if self.may_have_promoted {
+            // a queen moves like a rook or a bishop too, so only the knight needs adding
+            types.extend_from_slice(&[PieceType::Queen, PieceType::Knight]);
+        }
+        types
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board_state::square::sq;
+    use crate::board_state::Player;
+
+    #[test]
+    fn new_is_on_its_start_square() {
+        let tracked = TrackedPiece::new(Piece::new(PieceType::Knight, Player::Black), sq("g8"));
+
+        assert_eq!(tracked.get_squares(), vec![sq("g8")]);
+        assert_eq!(tracked.probability_at(sq("g8")), 1.0);
+        assert_eq!(tracked.probability_at(sq("f6")), 0.0);
+    }
+
+    #[test]
+    fn probability_spread_over_squares() {
+        let mut tracked = TrackedPiece::new(Piece::new(PieceType::Knight, Player::Black), sq("g8"));
+        tracked.squares |= Bitboards::bit(sq("f6")) | Bitboards::bit(sq("h6")) | Bitboards::bit(sq("e7"));
+
+        assert_eq!(tracked.probability_at(sq("f6")), 0.25);
+    }
+
+    #[test]
+    fn promoted_pawn_could_be_a_queen_but_not_a_king() {
+        let mut tracked = TrackedPiece::new(Piece::new(PieceType::Pawn, Player::White), sq("a2"));
+        assert!(!tracked.could_be(PieceType::Queen));
+
+        tracked.may_have_promoted = true;
+
+        assert!(tracked.could_be(PieceType::Queen));
+        assert!(tracked.could_be(PieceType::Pawn));
+        assert!(!tracked.could_be(PieceType::King));
+    }
+}
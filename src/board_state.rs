@@ -2,11 +2,41 @@ pub mod player;
 pub mod piece;
 pub mod tile;
 pub mod stored_move;
+pub mod castling_rights;
+pub mod move_error;
+pub mod move_record;
+pub mod player_view;
+pub mod game_status;
+pub mod game_rules;
+pub mod undo_info;
+pub mod square;
+pub mod bitboards;
+pub mod fen_error;
+pub(crate) mod fen;
+pub mod san_error;
+mod san;
+pub mod uci_error;
+mod uci;
+mod zobrist;
 
 pub use crate::board_state::player::Player;
 pub use crate::board_state::piece::{ Piece, PieceType };
 pub use crate::board_state::tile::Tile;
 pub use crate::board_state::stored_move::StoredMove;
+pub use crate::board_state::castling_rights::CastlingRights;
+pub use crate::board_state::move_error::MoveError;
+pub use crate::board_state::move_record::MoveRecord;
+pub use crate::board_state::player_view::{ PlayerView, ViewTile };
+pub use crate::board_state::game_status::{ GameStatus, DrawReason };
+pub use crate::board_state::game_rules::{ GameRules, FiftyMoveMode, RepetitionMode };
+pub use crate::board_state::undo_info::UndoInfo;
+pub use crate::board_state::square::Square;
+pub use crate::board_state::bitboards::Bitboards;
+pub use crate::board_state::fen_error::FenError;
+pub use crate::board_state::san_error::SanError;
+pub use crate::board_state::uci_error::UciError;
+
+use crate::move_generation::MoveGeneration;
 
 const WHITE: Player = Player::White;
 const BLACK: Player = Player::Black;
@@ -18,40 +48,181 @@ const KNIGHT: PieceType = PieceType::Knight;
 const BISHOP: PieceType = PieceType::Bishop;
 const PAWN: PieceType = PieceType::Pawn;
 
-#[derive(Debug)]
+// the fifty move rule counts the moves of both players
+const FIFTY_MOVE_RULE_LIMIT: usize = 100;
+const SEVENTY_FIVE_MOVE_RULE_LIMIT: usize = 150;
+const REPETITION_LIMIT: usize = 3;
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct BoardState {
     player_turn: Player,
     fifty_move_rule_count: usize,
-    board: [[Tile; 8]; 8],
+    // starts at 1 and goes up after each of black's moves
+    fullmove_number: usize,
+    castling_rights: CastlingRights,
+    en_passant: Option<Square>,
+    game_status: GameStatus,
+    rules: GameRules,
+    // the hash keys of every position before the current one
+    position_history: Vec<u64>,
+    // the tile on every square, indexed by Square::index(), so reading a tile is a single lookup
+    mailbox: [Tile; 64],
+    // the same pieces as the mailbox, for move generation. set_tile keeps the two in step
+    bitboards: Bitboards,
+    // the Zobrist key of the position, see zobrist.rs
+    hash_key: u64,
 }
 
-// TODO track en passants and whether castling is legal for each player
 impl BoardState {
     pub fn new() -> BoardState {
-        BoardState {
+        BoardState::with_rules(GameRules::new())
+    }
+
+    pub fn with_rules(rules: GameRules) -> BoardState {
+        let mailbox = BoardState::place_pieces();
+
+        let mut board_state = BoardState {
             player_turn: Player::White,
             fifty_move_rule_count: 0,
-            board: BoardState::place_pieces()
+            fullmove_number: 1,
+            castling_rights: CastlingRights::new(),
+            en_passant: None,
+            game_status: GameStatus::Ongoing,
+            rules,
+            position_history: Vec::new(),
+            mailbox,
+            bitboards: Bitboards::from_tiles(&mailbox),
+            hash_key: 0,
+        };
+        board_state.hash_key = board_state.compute_hash_key();
+        board_state
+    }
+
+    pub fn get_player_turn(&self) -> &Player {
+        &self.player_turn
+    }
+
+    pub fn get_fifty_move_rule_count(&self) -> usize {
+        self.fifty_move_rule_count
+    }
+
+    pub fn get_fullmove_number(&self) -> usize {
+        self.fullmove_number
+    }
+
+    pub fn get_rules(&self) -> &GameRules {
+        &self.rules
+    }
+
+    // whether fifty moves each have passed without a pawn move or capture
+    pub fn is_fifty_move_draw(&self) -> bool {
+        self.fifty_move_rule_count >= FIFTY_MOVE_RULE_LIMIT
+    }
+
+    // ends the game in a draw if the fifty move rule allows it, and returns whether it did
+    pub fn claim_fifty_move_draw(&mut self) -> bool {
+        if self.game_status.is_over() || !self.is_fifty_move_draw() {
+            return false;
         }
+
+        self.game_status = GameStatus::Draw(DrawReason::FiftyMoveRule);
+        true
+    }
+
+    // how many times the current position has occurred, including now
+    pub fn repetition_count(&self) -> usize {
+        1 + self.position_history.iter().filter(|key| **key == self.hash_key).count()
+    }
+
+    pub fn get_game_status(&self) -> &GameStatus {
+        &self.game_status
+    }
+
+    // resigning hands the win to the other player, and does nothing once the game is over
+    pub fn resign(&mut self, player: Player) {
+        if !self.game_status.is_over() {
+            self.game_status = GameStatus::win_for(player.opponent());
+        }
+    }
+
+    pub fn get_castling_rights(&self) -> &CastlingRights {
+        &self.castling_rights
+    }
+
+    // the tile a pawn skipped over with a two step on the last move, if any
+    // this is the tile an enemy pawn lands on when capturing en passant
+    pub fn get_en_passant(&self) -> &Option<Square> {
+        &self.en_passant
+    }
+
+    // the tile of the pawn that can be captured en passant, if any
+    // under fog of war, a player with a pawn able to capture en passant sees the en passant
+    // tile (empty, since it is a tile they can move to) and also sees this pawn, since capturing
+    // it is part of the move. a player without such a pawn sees neither because of en passant
+    pub fn get_en_passant_pawn_pos(&self) -> Option<Square> {
+        self.en_passant.and_then(|pos| match pos.get_rank() {
+            // white skipped rank 3 and landed on rank 4, black skipped rank 6 and landed on rank 5
+            2 => pos.offset(0, 1),
+            _ => pos.offset(0, -1),
+        })
     }
 
     // TODO should this instead be a reference to?
-    pub fn get_pos_of_tile<'a>(&self, tile: &'a Tile) -> &'a (usize, usize) {
-        &tile.get_pos()
+    pub fn get_pos_of_tile<'a>(&self, tile: &'a Tile) -> &'a Square {
+        tile.get_pos()
     }
 
     // TODO should this instead be a reference to?
-    pub fn get_tile_at_pos(&self, pos: (usize, usize)) -> &Tile {
-        let (x, y) = pos;
-        &self.board[x][y]
+    pub fn get_tile_at_pos(&self, pos: Square) -> &Tile {
+        &self.mailbox[pos.index()]
+    }
+
+    pub fn get_bitboards(&self) -> &Bitboards {
+        &self.bitboards
+    }
+
+    fn set_tile(&mut self, piece: Option<Piece>, pos: Square) {
+        self.hash_key ^= BoardState::piece_hash_key(*self.get_tile_at_pos(pos).get_piece(), pos)
+            ^ BoardState::piece_hash_key(piece, pos);
+        self.mailbox[pos.index()] = Tile::new(piece, pos);
+        self.bitboards.set(pos, piece);
+    }
+
+    // builds a board with no pieces on it, useful for setting up specific positions
+    pub(crate) fn empty() -> BoardState {
+        let mut mailbox = [Tile::new(None, Square::new(0, 0).unwrap()); 64];
+        for pos in Square::all() {
+            mailbox[pos.index()] = Tile::new(None, pos);
+        }
+
+        BoardState {
+            player_turn: Player::White,
+            fifty_move_rule_count: 0,
+            fullmove_number: 1,
+            castling_rights: CastlingRights::none(),
+            en_passant: None,
+            game_status: GameStatus::Ongoing,
+            rules: GameRules::new(),
+            position_history: Vec::new(),
+            mailbox,
+            bitboards: Bitboards::new(),
+            // no pieces, no castling rights and white to move
+            hash_key: 0,
+        }
+    }
+
+    // puts a piece on a tile, or clears it, without any of the bookkeeping of move_piece
+    pub(crate) fn set_piece_at_pos(&mut self, piece: Option<Piece>, pos: Square) {
+        self.set_tile(piece, pos);
     }
 
     pub fn display_full_board(&self) -> String {
         let mut result = String::from("");
 
-        for x in self.board.iter() {
-            for y in x.iter() {
-                result.push(y.symbol());
+        // rank 8 is printed first, so white is at the bottom
+        for rank in self.mailbox.chunks(8).rev() {
+            for tile in rank {
+                result.push(tile.symbol());
                 result.push(' ');
             }
             result.pop(); // To remove extra space
@@ -65,9 +236,9 @@ impl BoardState {
     pub fn display_full_board_utf(&self) -> String {
         let mut result = String::from("");
 
-        for x in self.board.iter() {
-            for y in x.iter() {
-                result.push(y.symbol_utf());
+        for rank in self.mailbox.chunks(8).rev() {
+            for tile in rank {
+                result.push(tile.symbol_utf());
                 result.push(' ');
             }
             result.pop(); // To remove extra space
@@ -78,46 +249,270 @@ impl BoardState {
         result
     }
 
-    // TODO commented out player and seperated the lines just because the warning was annoying me
-    pub fn move_piece(&mut self, 
-        // player: Player, 
-        planned_move: StoredMove) {
-        let (start_y, start_x) = planned_move.start_pos;
-        let (end_y, end_x) = planned_move.end_pos;
-        let promotion = planned_move.promotion;
+    // the tiles player can see, indexed [rank][file] like the board
+    // a player sees every tile holding one of their pieces and every tile one of their pieces can move to.
+    // pawns are special: a diagonal is only seen when it can be captured, and the tile in front is seen
+    // even when it is blocked. an en passant capture also reveals the pawn that would be captured
+    pub fn visible_tiles(&self, player: Player) -> [[bool; 8]; 8] {
+        let mut visible = [[false; 8]; 8];
+
+        for pos in Bitboards::squares(self.bitboards.get_player_pieces(player)) {
+            let tile = self.get_tile_at_pos(pos);
+            let piece = tile.get_piece().unwrap();
+            let mut seen = vec![*tile.get_pos()];
+
+            for stored_move in MoveGeneration::gen_reachable_tile(tile, self) {
+                seen.push(stored_move.end_pos);
+
+                if piece.get_piece_type() == &PAWN && Some(stored_move.end_pos) == self.en_passant {
+                    seen.extend(self.get_en_passant_pawn_pos());
+                }
+            }
+
+            if piece.get_piece_type() == &PAWN {
+                seen.extend(tile.get_pos().offset(0, player.forward()));
+            }
+
+            for pos in seen {
+                visible[pos.get_rank()][pos.get_file()] = true;
+            }
+        }
+
+        visible
+    }
+
+    // checks that planned_move is a legal move for player, and if so plays it and passes the turn
+    // move is legal if -
+    // the game is not over
+    // there is a piece in start, and it belongs to the player whose turn it is
+    // the piece can move to end per the rules (knights, bishops, etc), see MoveGeneration
+    // note! since dark chess, checking self is allowed
+    pub fn try_move(&mut self, player: Player, planned_move: StoredMove) -> Result<MoveRecord, MoveError> {
+        if self.game_status.is_over() {
+            return Err(MoveError::GameOver);
+        }
+
+        let start_tile = self.get_tile_at_pos(planned_move.start_pos);
+        let moved_piece = match start_tile.get_piece() {
+            Some(piece) => *piece,
+            None => return Err(MoveError::EmptyStart),
+        };
+
+        if player != self.player_turn {
+            return Err(MoveError::NotYourTurn);
+        }
+        if moved_piece.get_player() != &player {
+            return Err(MoveError::NotYourPiece);
+        }
+
+        if !MoveGeneration::gen_moves_tile(start_tile, self).contains(&planned_move) {
+            return Err(MoveError::IllegalDestination);
+        }
+
+        let record = MoveRecord {
+            stored_move: planned_move,
+            player,
+            moved_piece,
+            captured: self.captured_by(&planned_move),
+        };
+
+        self.move_piece(planned_move);
+
+        Ok(record)
+    }
+
+    // the piece that would be captured by planned_move, along with where it is
+    fn captured_by(&self, planned_move: &StoredMove) -> Option<(Piece, Square)> {
+        if let Some(piece) = self.get_tile_at_pos(planned_move.end_pos).get_piece() {
+            return Some((*piece, planned_move.end_pos));
+        }
 
-        // TODO ensure that move is within the bounds of the board
-        // TODO ensure that a move is legal
+        let moved_piece = self.get_tile_at_pos(planned_move.start_pos).get_piece();
+        let is_pawn = matches!(moved_piece, Some(piece) if piece.get_piece_type() == &PAWN);
+        if is_pawn && Some(planned_move.end_pos) == self.en_passant {
+            let captured_pos = self.get_en_passant_pawn_pos().unwrap();
+            return self.get_tile_at_pos(captured_pos).get_piece().map(|piece| (piece, captured_pos));
+        }
 
-        // move is legal if - 
-        // player's turn
-        // grabbing a piece in start
-        // grabbing a piece of the players type in start
-        // moving to a valid position per rules (knights, bishops, etc) in end
-        // note! since dark chess, checking self is allowed
-        // end is either a none or an enemy
+        None
+    }
 
-        let start_tile = self.board[start_x][start_y];
-        let start_piece = start_tile.get_piece();
+    // moves a piece without checking whether the move is legal, see try_move for that
+    // this also passes the turn and keeps the castling rights, en passant, fifty move count,
+    // position history and game status up to date
+    pub fn move_piece(&mut self, planned_move: StoredMove) {
+        let start = planned_move.start_pos;
+        let end = planned_move.end_pos;
 
-        let end_tile = self.board[end_x][end_y];
+        let start_piece = *self.get_tile_at_pos(start).get_piece();
+        let captured = self.captured_by(&planned_move);
+        self.position_history.push(self.hash_key);
+        self.hash_key ^= self.state_hash_key();
 
         // TODO is there a way to not have to rewrite the Some(piece)
-        // TODO should I really be dereferencing here?
-        self.board[end_x][end_y] = match promotion {
-            Some(piece) => Tile::new(Some(piece), (end_x, end_y)),
-            None => Tile::new(*start_piece, planned_move.end_pos),
+        match planned_move.promotion {
+            Some(piece) => self.set_tile(Some(piece), end),
+            None => self.set_tile(start_piece, end),
+        };
+        self.set_tile(None, start);
+
+        let mut en_passant = None;
+        if let Some(piece) = start_piece {
+            // a king moving two files is a castle, so the rook has to jump over it
+            if piece.get_piece_type() == &KING && (start.get_file() as i32 - end.get_file() as i32).abs() == 2 {
+                self.move_castling_rook(end);
+            }
+
+            if piece.get_piece_type() == &PAWN {
+                // a pawn moving onto the en passant tile captures the pawn that skipped over it
+                if Some(end) == self.en_passant {
+                    let captured_pos = self.get_en_passant_pawn_pos().unwrap();
+                    self.set_tile(None, captured_pos);
+                }
+
+                // a two step leaves the skipped tile open to en passant for the next move only
+                if (start.get_rank() as i32 - end.get_rank() as i32).abs() == 2 {
+                    en_passant = Square::new(start.get_file(), (start.get_rank() + end.get_rank()) / 2);
+                }
+            }
+
+            self.update_castling_rights(piece, &planned_move);
+        }
+        self.en_passant = en_passant;
+
+        // the fifty move count restarts whenever a pawn moves or a piece is captured
+        let pawn_moved = matches!(start_piece, Some(piece) if piece.get_piece_type() == &PAWN);
+        if pawn_moved || captured.is_some() {
+            self.fifty_move_rule_count = 0;
+        } else {
+            self.fifty_move_rule_count += 1;
+        }
+
+        if self.player_turn == Player::Black {
+            self.fullmove_number += 1;
+        }
+        self.player_turn = self.player_turn.opponent();
+        self.hash_key ^= self.state_hash_key();
+
+        self.update_game_status(captured.map(|(piece, _)| piece));
+    }
+
+    // plays a move like move_piece, and returns what is needed to take it back with unmake_move
+    // panics if there is no piece on the starting tile
+    pub fn make_move(&mut self, planned_move: StoredMove) -> UndoInfo {
+        let undo_info = UndoInfo {
+            stored_move: planned_move,
+            moved_piece: self.get_tile_at_pos(planned_move.start_pos).get_piece().unwrap(),
+            captured: self.captured_by(&planned_move),
+            castling_rights: self.castling_rights,
+            en_passant: self.en_passant,
+            fifty_move_rule_count: self.fifty_move_rule_count,
+            fullmove_number: self.fullmove_number,
+            game_status: self.game_status,
         };
-        self.board[start_x][start_y] = Tile::new(None, (start_x, start_y));
 
-        println!();
-        println!("start piece: {:?}, end piece: {:?}", start_tile, end_tile);
-        println!("move_piece()!");
-        println!();
+        self.move_piece(planned_move);
+
+        undo_info
+    }
+
+    // takes back the last move played with make_move, leaving the board exactly as it was before it
+    // undo records have to be unmade in the reverse order they were made
+    pub fn unmake_move(&mut self, undo_info: UndoInfo) {
+        let start = undo_info.stored_move.start_pos;
+        let end = undo_info.stored_move.end_pos;
+
+        self.set_tile(None, end);
+        self.set_tile(Some(undo_info.moved_piece), start);
+        if let Some((piece, pos)) = undo_info.captured {
+            self.set_tile(Some(piece), pos);
+        }
+
+        // the rook goes back to its corner, the reverse of move_castling_rook
+        let files_moved = (start.get_file() as i32 - end.get_file() as i32).abs();
+        if undo_info.moved_piece.get_piece_type() == &KING && files_moved == 2 {
+            let (rook_start, rook_end) = BoardState::castling_rook_squares(end);
+
+            let rook = *self.get_tile_at_pos(rook_end).get_piece();
+            self.set_tile(rook, rook_start);
+            self.set_tile(None, rook_end);
+        }
+
+        self.hash_key ^= self.state_hash_key();
+        self.castling_rights = undo_info.castling_rights;
+        self.en_passant = undo_info.en_passant;
+        self.fifty_move_rule_count = undo_info.fifty_move_rule_count;
+        self.fullmove_number = undo_info.fullmove_number;
+        self.game_status = undo_info.game_status;
+        self.player_turn = *undo_info.moved_piece.get_player();
+        self.hash_key ^= self.state_hash_key();
+        self.position_history.pop();
+    }
+
+    // capturing a king wins the game, which takes priority over a draw on the same move
+    fn update_game_status(&mut self, captured: Option<Piece>) {
+        if self.game_status.is_over() {
+            return;
+        }
+
+        if let Some(piece) = captured {
+            if piece.get_piece_type() == &KING {
+                self.game_status = GameStatus::win_for(piece.get_player().opponent());
+                return;
+            }
+        }
+
+        if self.rules.fifty_move_mode == FiftyMoveMode::Automatic
+            && self.fifty_move_rule_count >= SEVENTY_FIVE_MOVE_RULE_LIMIT {
+            self.game_status = GameStatus::Draw(DrawReason::SeventyFiveMoveRule);
+        }
+
+        if self.rules.repetition_mode == RepetitionMode::TruePosition
+            && self.repetition_count() >= REPETITION_LIMIT {
+            self.game_status = GameStatus::Draw(DrawReason::ThreefoldRepetition);
+        }
+    }
+
+    // moves the rook next to the king, given where the king landed after castling
+    fn move_castling_rook(&mut self, king_end_pos: Square) {
+        let (rook_start, rook_end) = BoardState::castling_rook_squares(king_end_pos);
+
+        let rook = *self.get_tile_at_pos(rook_start).get_piece();
+        self.set_tile(rook, rook_end);
+        self.set_tile(None, rook_start);
+    }
+
+    // where the castling rook starts and ends, given where the king landed
+    fn castling_rook_squares(king_end_pos: Square) -> (Square, Square) {
+        let (rook_start_file, rook_end_file) = if king_end_pos.get_file() == 6 { (7, 5) } else { (0, 3) };
+
+        (
+            Square::new(rook_start_file, king_end_pos.get_rank()).unwrap(),
+            Square::new(rook_end_file, king_end_pos.get_rank()).unwrap(),
+        )
+    }
+
+    // a king moving loses both castles, and anything moving from or onto a rook's
+    // starting corner loses that castle, which covers both rook moves and rook captures
+    fn update_castling_rights(&mut self, piece: Piece, planned_move: &StoredMove) {
+        if piece.get_piece_type() == &KING {
+            self.castling_rights.revoke_all(*piece.get_player());
+        }
+
+        for pos in [planned_move.start_pos, planned_move.end_pos] {
+            match (pos.get_file(), pos.get_rank()) {
+                (0, 0) => self.castling_rights.revoke_queenside(WHITE),
+                (7, 0) => self.castling_rights.revoke_kingside(WHITE),
+                (0, 7) => self.castling_rights.revoke_queenside(BLACK),
+                (7, 7) => self.castling_rights.revoke_kingside(BLACK),
+                _ => (),
+            }
+        }
     }
 
     // TODO this function is super ugly, and doing a lot
-    fn place_pieces() -> [[Tile; 8]; 8] {
+    // the mailbox for the starting position
+    fn place_pieces() -> [Tile; 64] {
         // Putting these declarations up top instead of in the array makes the code more readable, imo
 
         // There is a crate that allows me to input a closure to init the array,
@@ -138,46 +533,684 @@ impl BoardState {
         let b_bishop = Piece::new(BISHOP, BLACK);
         let b_pawn = Piece::new(PAWN, BLACK);
 
-        // declaring rows
-        let row_8 = [
-            Tile::new(Some(b_rook), (0,0)),
-            Tile::new(Some(b_knight), (0,1)),
-            Tile::new(Some(b_bishop), (0,2)),
-            Tile::new(Some(b_queen), (0,3)),
-            Tile::new(Some(b_king), (0,4)),
-            Tile::new(Some(b_bishop), (0,5)),
-            Tile::new(Some(b_knight), (0,6)),
-            Tile::new(Some(b_rook), (0,7)),
-        ];
-        let row_7 = BoardState::init_consistent_row(1, Some(b_pawn));
-
-        let row_6 = BoardState::init_consistent_row(2, None);
-        let row_5 = BoardState::init_consistent_row(3, None);
-        let row_4 = BoardState::init_consistent_row(4, None);
-        let row_3 = BoardState::init_consistent_row(5, None);
-
-        let row_2 = BoardState::init_consistent_row(6, Some(w_pawn));
-        let row_1 = [
-            Tile::new(Some(w_rook), (7,0)),
-            Tile::new(Some(w_knight), (7,1)),
-            Tile::new(Some(w_bishop), (7,2)),
-            Tile::new(Some(w_queen), (7,3)),
-            Tile::new(Some(w_king), (7,4)),
-            Tile::new(Some(w_bishop), (7,5)),
-            Tile::new(Some(w_knight), (7,6)),
-            Tile::new(Some(w_rook), (7,7)),
-        ];
+        // declaring ranks
+        let back_rank = |rank, king, queen, rook, knight, bishop| {
+            let pieces = [rook, knight, bishop, queen, king, bishop, knight, rook];
+            let mut rank_arr = [Tile::new(None, Square::new(0, rank).unwrap()); 8];
+            for (file, val) in rank_arr.iter_mut().enumerate() {
+                *val = Tile::new(Some(pieces[file]), Square::new(file, rank).unwrap());
+            }
+            rank_arr
+        };
+
+        let rank_1 = back_rank(0, w_king, w_queen, w_rook, w_knight, w_bishop);
+        let rank_2 = BoardState::init_consistent_row(1, Some(w_pawn));
+
+        let rank_3 = BoardState::init_consistent_row(2, None);
+        let rank_4 = BoardState::init_consistent_row(3, None);
+        let rank_5 = BoardState::init_consistent_row(4, None);
+        let rank_6 = BoardState::init_consistent_row(5, None);
+
+        let rank_7 = BoardState::init_consistent_row(6, Some(b_pawn));
+        let rank_8 = back_rank(7, b_king, b_queen, b_rook, b_knight, b_bishop);
 
-        [ row_8, row_7, row_6, row_5, row_4, row_3, row_2, row_1 ]
+        let mut mailbox = [rank_1[0]; 64];
+        for tile in [ rank_1, rank_2, rank_3, rank_4, rank_5, rank_6, rank_7, rank_8 ].iter().flatten() {
+            mailbox[tile.get_pos().index()] = *tile;
+        }
+        mailbox
     }
     
-    fn init_consistent_row(row: usize, piece: Option<Piece>) -> [Tile; 8] {
-        let mut row_arr = [Tile::new(piece, (0, 0)); 8];
-        for (index, mut _val) in row_arr.iter_mut().enumerate() {
-            *_val = Tile::new(piece, (row, index));
+    fn init_consistent_row(rank: usize, piece: Option<Piece>) -> [Tile; 8] {
+        let mut row_arr = [Tile::new(piece, Square::new(0, rank).unwrap()); 8];
+        for (file, val) in row_arr.iter_mut().enumerate() {
+            *val = Tile::new(piece, Square::new(file, rank).unwrap());
         }
         row_arr
     }
 }
 
-// TODO test suite?
\ No newline at end of file
+impl Default for BoardState {
+    fn default() -> Self {
+        BoardState::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board_state::square::sq;
+
+    fn castle_board() -> BoardState {
+        let mut board_state = BoardState::new();
+        for file in [1, 2, 3, 5, 6] {
+            board_state.set_piece_at_pos(None, Square::new(file, 0).unwrap());
+            board_state.set_piece_at_pos(None, Square::new(file, 7).unwrap());
+        }
+        board_state
+    }
+
+    #[test]
+    fn new_has_all_castling_rights() {
+        let board_state = BoardState::new();
+
+        assert_eq!(board_state.get_castling_rights(), &CastlingRights::new());
+    }
+
+    #[test]
+    fn castle_kingside_moves_king_and_rook() {
+        let mut board_state = castle_board();
+
+        board_state.move_piece(StoredMove { start_pos: sq("e1"), end_pos: sq("g1"), promotion: None });
+
+        assert_eq!(board_state.get_tile_at_pos(sq("g1")).get_piece(), &Some(Piece::new(KING, WHITE)));
+        assert_eq!(board_state.get_tile_at_pos(sq("f1")).get_piece(), &Some(Piece::new(ROOK, WHITE)));
+        assert_eq!(board_state.get_tile_at_pos(sq("e1")).get_piece(), &None);
+        assert_eq!(board_state.get_tile_at_pos(sq("h1")).get_piece(), &None);
+    }
+
+    #[test]
+    fn castle_queenside_moves_king_and_rook() {
+        let mut board_state = castle_board();
+
+        board_state.move_piece(StoredMove { start_pos: sq("e8"), end_pos: sq("c8"), promotion: None });
+
+        assert_eq!(board_state.get_tile_at_pos(sq("c8")).get_piece(), &Some(Piece::new(KING, BLACK)));
+        assert_eq!(board_state.get_tile_at_pos(sq("d8")).get_piece(), &Some(Piece::new(ROOK, BLACK)));
+        assert_eq!(board_state.get_tile_at_pos(sq("e8")).get_piece(), &None);
+        assert_eq!(board_state.get_tile_at_pos(sq("a8")).get_piece(), &None);
+    }
+
+    #[test]
+    fn king_move_revokes_both_castles() {
+        let mut board_state = castle_board();
+
+        board_state.move_piece(StoredMove { start_pos: sq("e1"), end_pos: sq("f1"), promotion: None });
+
+        assert!(!board_state.get_castling_rights().can_castle_kingside(WHITE));
+        assert!(!board_state.get_castling_rights().can_castle_queenside(WHITE));
+        assert!(board_state.get_castling_rights().can_castle_kingside(BLACK));
+    }
+
+    #[test]
+    fn rook_move_revokes_one_castle() {
+        let mut board_state = castle_board();
+
+        board_state.move_piece(StoredMove { start_pos: sq("a1"), end_pos: sq("b1"), promotion: None });
+
+        assert!(!board_state.get_castling_rights().can_castle_queenside(WHITE));
+        assert!(board_state.get_castling_rights().can_castle_kingside(WHITE));
+    }
+
+    #[test]
+    fn rook_capture_revokes_castle() {
+        let mut board_state = castle_board();
+        board_state.set_piece_at_pos(Some(Piece::new(KNIGHT, WHITE)), sq("g6"));
+
+        board_state.move_piece(StoredMove { start_pos: sq("g6"), end_pos: sq("h8"), promotion: None });
+
+        assert!(!board_state.get_castling_rights().can_castle_kingside(BLACK));
+        assert!(board_state.get_castling_rights().can_castle_queenside(BLACK));
+    }
+
+    #[test]
+    fn new_has_no_en_passant() {
+        let board_state = BoardState::new();
+
+        assert_eq!(board_state.get_en_passant(), &None);
+        assert_eq!(board_state.get_en_passant_pawn_pos(), None);
+    }
+
+    #[test]
+    fn two_step_sets_en_passant() {
+        let mut board_state = BoardState::new();
+
+        board_state.move_piece(StoredMove { start_pos: sq("e2"), end_pos: sq("e4"), promotion: None });
+
+        assert_eq!(board_state.get_en_passant(), &Some(sq("e3")));
+        assert_eq!(board_state.get_en_passant_pawn_pos(), Some(sq("e4")));
+    }
+
+    #[test]
+    fn black_two_step_sets_en_passant() {
+        let mut board_state = BoardState::new();
+
+        board_state.move_piece(StoredMove { start_pos: sq("d7"), end_pos: sq("d5"), promotion: None });
+
+        assert_eq!(board_state.get_en_passant(), &Some(sq("d6")));
+        assert_eq!(board_state.get_en_passant_pawn_pos(), Some(sq("d5")));
+    }
+
+    #[test]
+    fn one_step_does_not_set_en_passant() {
+        let mut board_state = BoardState::new();
+
+        board_state.move_piece(StoredMove { start_pos: sq("e2"), end_pos: sq("e3"), promotion: None });
+
+        assert_eq!(board_state.get_en_passant(), &None);
+    }
+
+    #[test]
+    fn en_passant_cleared_after_next_move() {
+        let mut board_state = BoardState::new();
+
+        board_state.move_piece(StoredMove { start_pos: sq("e2"), end_pos: sq("e4"), promotion: None });
+        board_state.move_piece(StoredMove { start_pos: sq("b8"), end_pos: sq("c6"), promotion: None });
+
+        assert_eq!(board_state.get_en_passant(), &None);
+    }
+
+    #[test]
+    fn en_passant_capture_removes_pawn() {
+        let mut board_state = BoardState::new();
+        board_state.set_piece_at_pos(Some(Piece::new(PAWN, BLACK)), sq("d4"));
+
+        board_state.move_piece(StoredMove { start_pos: sq("e2"), end_pos: sq("e4"), promotion: None });
+        board_state.move_piece(StoredMove { start_pos: sq("d4"), end_pos: sq("e3"), promotion: None });
+
+        assert_eq!(board_state.get_tile_at_pos(sq("e3")).get_piece(), &Some(Piece::new(PAWN, BLACK)));
+        assert_eq!(board_state.get_tile_at_pos(sq("e4")).get_piece(), &None);
+        assert_eq!(board_state.get_tile_at_pos(sq("d4")).get_piece(), &None);
+        assert_eq!(board_state.get_en_passant(), &None);
+    }
+
+    #[test]
+    fn try_move_plays_and_passes_turn() {
+        let mut board_state = BoardState::new();
+        let planned_move = StoredMove { start_pos: sq("g1"), end_pos: sq("f3"), promotion: None };
+
+        let record = board_state.try_move(WHITE, planned_move);
+
+        assert_eq!(record, Ok(MoveRecord {
+            stored_move: planned_move,
+            player: WHITE,
+            moved_piece: Piece::new(KNIGHT, WHITE),
+            captured: None,
+        }));
+        assert_eq!(board_state.get_tile_at_pos(sq("f3")).get_piece(), &Some(Piece::new(KNIGHT, WHITE)));
+        assert_eq!(board_state.get_player_turn(), &BLACK);
+        assert_eq!(board_state.get_fifty_move_rule_count(), 1);
+    }
+
+    #[test]
+    fn try_move_empty_start() {
+        let mut board_state = BoardState::new();
+
+        let result = board_state.try_move(WHITE, StoredMove { start_pos: sq("e4"), end_pos: sq("e5"), promotion: None });
+
+        assert_eq!(result, Err(MoveError::EmptyStart));
+    }
+
+    #[test]
+    fn try_move_not_your_turn() {
+        let mut board_state = BoardState::new();
+
+        let result = board_state.try_move(BLACK, StoredMove { start_pos: sq("e7"), end_pos: sq("e5"), promotion: None });
+
+        assert_eq!(result, Err(MoveError::NotYourTurn));
+    }
+
+    #[test]
+    fn try_move_not_your_piece() {
+        let mut board_state = BoardState::new();
+
+        let result = board_state.try_move(WHITE, StoredMove { start_pos: sq("e7"), end_pos: sq("e5"), promotion: None });
+
+        assert_eq!(result, Err(MoveError::NotYourPiece));
+    }
+
+    #[test]
+    fn try_move_illegal_destination() {
+        let mut board_state = BoardState::new();
+
+        let result = board_state.try_move(WHITE, StoredMove { start_pos: sq("e2"), end_pos: sq("e5"), promotion: None });
+
+        assert_eq!(result, Err(MoveError::IllegalDestination));
+        assert_eq!(board_state.get_tile_at_pos(sq("e2")).get_piece(), &Some(Piece::new(PAWN, WHITE)));
+        assert_eq!(board_state.get_player_turn(), &WHITE);
+    }
+
+    #[test]
+    fn try_move_game_over() {
+        let mut board_state = BoardState::new();
+        board_state.resign(BLACK);
+
+        let result = board_state.try_move(WHITE, StoredMove { start_pos: sq("e2"), end_pos: sq("e4"), promotion: None });
+
+        assert_eq!(result, Err(MoveError::GameOver));
+    }
+
+    #[test]
+    fn try_move_records_capture_and_resets_fifty_move_count() {
+        let mut board_state = BoardState::new();
+        board_state.set_piece_at_pos(Some(Piece::new(KNIGHT, BLACK)), sq("e3"));
+        board_state.try_move(WHITE, StoredMove { start_pos: sq("g1"), end_pos: sq("h3"), promotion: None }).unwrap();
+        board_state.try_move(BLACK, StoredMove { start_pos: sq("g8"), end_pos: sq("h6"), promotion: None }).unwrap();
+
+        let record = board_state.try_move(WHITE, StoredMove { start_pos: sq("d2"), end_pos: sq("e3"), promotion: None }).unwrap();
+
+        assert_eq!(record.captured, Some((Piece::new(KNIGHT, BLACK), sq("e3"))));
+        assert_eq!(board_state.get_fifty_move_rule_count(), 0);
+    }
+
+    #[test]
+    fn try_move_records_en_passant_capture() {
+        let mut board_state = BoardState::new();
+        board_state.set_piece_at_pos(Some(Piece::new(PAWN, WHITE)), sq("d5"));
+        board_state.try_move(WHITE, StoredMove { start_pos: sq("g1"), end_pos: sq("f3"), promotion: None }).unwrap();
+        board_state.try_move(BLACK, StoredMove { start_pos: sq("e7"), end_pos: sq("e5"), promotion: None }).unwrap();
+
+        let record = board_state.try_move(WHITE, StoredMove { start_pos: sq("d5"), end_pos: sq("e6"), promotion: None }).unwrap();
+
+        assert_eq!(record.captured, Some((Piece::new(PAWN, BLACK), sq("e5"))));
+        assert_eq!(board_state.get_tile_at_pos(sq("e5")).get_piece(), &None);
+    }
+
+    #[test]
+    fn try_move_promotion_needs_choice() {
+        let mut board_state = BoardState::new();
+        board_state.set_piece_at_pos(None, sq("a8"));
+        board_state.set_piece_at_pos(Some(Piece::new(PAWN, WHITE)), sq("a7"));
+
+        let result = board_state.try_move(WHITE, StoredMove { start_pos: sq("a7"), end_pos: sq("a8"), promotion: None });
+        assert_eq!(result, Err(MoveError::IllegalDestination));
+
+        let promotion = Some(Piece::new(QUEEN, WHITE));
+        board_state.try_move(WHITE, StoredMove { start_pos: sq("a7"), end_pos: sq("a8"), promotion }).unwrap();
+        assert_eq!(board_state.get_tile_at_pos(sq("a8")).get_piece(), &promotion);
+    }
+
+    fn visible_count(visible: &[[bool; 8]; 8]) -> usize {
+        visible.iter().flatten().filter(|seen| **seen).count()
+    }
+
+    #[test]
+    fn visible_tiles_starting_position() {
+        let board_state = BoardState::new();
+
+        let visible = board_state.visible_tiles(WHITE);
+
+        // the four rows on white's side of the board
+        for (rank, row) in visible.iter().enumerate() {
+            assert!(row.iter().all(|seen| *seen == (rank < 4)));
+        }
+    }
+
+    #[test]
+    fn visible_tiles_starting_position_black() {
+        let board_state = BoardState::new();
+
+        let visible = board_state.visible_tiles(BLACK);
+
+        assert_eq!(visible_count(&visible), 32);
+        assert!(visible[4].iter().all(|seen| *seen));
+        assert!(!visible[3].iter().any(|seen| *seen));
+    }
+
+    #[test]
+    fn visible_tiles_knight_moves() {
+        let mut board_state = BoardState::empty();
+        board_state.set_piece_at_pos(Some(Piece::new(KNIGHT, WHITE)), sq("a8"));
+
+        let visible = board_state.visible_tiles(WHITE);
+
+        assert_eq!(visible_count(&visible), 3);
+        assert!(visible[7][0] && visible[5][1] && visible[6][2]);
+    }
+
+    #[test]
+    fn visible_tiles_pawn_front_seen_when_blocked() {
+        let mut board_state = BoardState::empty();
+        board_state.set_piece_at_pos(Some(Piece::new(PAWN, WHITE)), sq("e2"));
+        board_state.set_piece_at_pos(Some(Piece::new(QUEEN, BLACK)), sq("e3"));
+
+        let visible = board_state.visible_tiles(WHITE);
+
+        assert_eq!(visible_count(&visible), 2);
+        assert!(visible[2][4]);
+        assert!(!visible[3][4]);
+    }
+
+    #[test]
+    fn visible_tiles_pawn_diagonal_only_when_capturable() {
+        let mut board_state = BoardState::empty();
+        board_state.set_piece_at_pos(Some(Piece::new(PAWN, BLACK)), sq("e5"));
+        board_state.set_piece_at_pos(Some(Piece::new(KNIGHT, WHITE)), sq("f4"));
+
+        let visible = board_state.visible_tiles(BLACK);
+
+        assert!(visible[4][4] && visible[3][4] && visible[3][5]);
+        assert!(!visible[3][3]);
+        assert_eq!(visible_count(&visible), 3);
+    }
+
+    #[test]
+    fn visible_tiles_en_passant_reveals_pawn() {
+        let mut board_state = BoardState::new();
+        board_state.set_piece_at_pos(Some(Piece::new(PAWN, BLACK)), sq("d4"));
+        board_state.move_piece(StoredMove { start_pos: sq("e2"), end_pos: sq("e4"), promotion: None });
+
+        let visible = board_state.visible_tiles(BLACK);
+
+        assert!(visible[2][4]);
+        assert!(visible[3][4]);
+        assert!(!visible[3][5]);
+    }
+
+    #[test]
+    fn visible_tiles_unchanged_when_game_ends() {
+        let mut board_state = BoardState::new();
+        let before = board_state.visible_tiles(WHITE);
+
+        board_state.resign(BLACK);
+
+        assert_eq!(board_state.visible_tiles(WHITE), before);
+        assert_eq!(visible_count(&board_state.visible_tiles(WHITE)), 32);
+    }
+
+    #[test]
+    fn visible_tiles_no_pieces() {
+        let board_state = BoardState::empty();
+
+        assert_eq!(visible_count(&board_state.visible_tiles(WHITE)), 0);
+    }
+
+    #[test]
+    fn new_is_ongoing() {
+        let board_state = BoardState::new();
+
+        assert_eq!(board_state.get_game_status(), &GameStatus::Ongoing);
+    }
+
+    #[test]
+    fn king_capture_wins() {
+        let mut board_state = BoardState::new();
+        board_state.set_piece_at_pos(Some(Piece::new(KNIGHT, WHITE)), sq("f6"));
+
+        board_state.move_piece(StoredMove { start_pos: sq("f6"), end_pos: sq("e8"), promotion: None });
+
+        assert_eq!(board_state.get_game_status(), &GameStatus::WhiteWins);
+    }
+
+    #[test]
+    fn black_king_capture_through_try_move() {
+        let mut board_state = BoardState::new();
+        board_state.set_piece_at_pos(Some(Piece::new(KNIGHT, BLACK)), sq("d3"));
+        board_state.try_move(WHITE, StoredMove { start_pos: sq("a2"), end_pos: sq("a3"), promotion: None }).unwrap();
+
+        board_state.try_move(BLACK, StoredMove { start_pos: sq("d3"), end_pos: sq("e1"), promotion: None }).unwrap();
+
+        assert_eq!(board_state.get_game_status(), &GameStatus::BlackWins);
+        let result = board_state.try_move(WHITE, StoredMove { start_pos: sq("b2"), end_pos: sq("b3"), promotion: None });
+        assert_eq!(result, Err(MoveError::GameOver));
+    }
+
+    #[test]
+    fn resign_gives_win_to_other_player() {
+        let mut board_state = BoardState::new();
+
+        board_state.resign(WHITE);
+
+        assert_eq!(board_state.get_game_status(), &GameStatus::BlackWins);
+    }
+
+    #[test]
+    fn resign_after_game_over_does_nothing() {
+        let mut board_state = BoardState::new();
+        board_state.resign(WHITE);
+
+        board_state.resign(BLACK);
+
+        assert_eq!(board_state.get_game_status(), &GameStatus::BlackWins);
+    }
+
+    fn knight_shuffle() -> [StoredMove; 4] {
+        [
+            StoredMove { start_pos: sq("g1"), end_pos: sq("f3"), promotion: None },
+            StoredMove { start_pos: sq("g8"), end_pos: sq("f6"), promotion: None },
+            StoredMove { start_pos: sq("f3"), end_pos: sq("g1"), promotion: None },
+            StoredMove { start_pos: sq("f6"), end_pos: sq("g8"), promotion: None },
+        ]
+    }
+
+    fn shuffle_knights(board_state: &mut BoardState, moves: usize) {
+        for stored_move in knight_shuffle().iter().cycle().take(moves) {
+            board_state.move_piece(*stored_move);
+        }
+    }
+
+    #[test]
+    fn fifty_move_rule_count_increments_and_resets() {
+        let mut board_state = BoardState::new();
+
+        shuffle_knights(&mut board_state, 3);
+        assert_eq!(board_state.get_fifty_move_rule_count(), 3);
+
+        board_state.move_piece(StoredMove { start_pos: sq("a7"), end_pos: sq("a5"), promotion: None });
+        assert_eq!(board_state.get_fifty_move_rule_count(), 0);
+    }
+
+    fn no_repetition_rules() -> GameRules {
+        GameRules { repetition_mode: RepetitionMode::Never, ..GameRules::new() }
+    }
+
+    #[test]
+    fn fifty_move_rule_not_reached() {
+        let mut board_state = BoardState::with_rules(no_repetition_rules());
+
+        shuffle_knights(&mut board_state, FIFTY_MOVE_RULE_LIMIT - 1);
+
+        assert!(!board_state.is_fifty_move_draw());
+        assert!(!board_state.claim_fifty_move_draw());
+        assert_eq!(board_state.get_game_status(), &GameStatus::Ongoing);
+    }
+
+    #[test]
+    fn fifty_move_rule_claim() {
+        let mut board_state = BoardState::with_rules(no_repetition_rules());
+
+        shuffle_knights(&mut board_state, FIFTY_MOVE_RULE_LIMIT);
+        assert!(board_state.is_fifty_move_draw());
+        assert_eq!(board_state.get_game_status(), &GameStatus::Ongoing);
+
+        assert!(board_state.claim_fifty_move_draw());
+        assert_eq!(board_state.get_game_status(), &GameStatus::Draw(DrawReason::FiftyMoveRule));
+    }
+
+    #[test]
+    fn fifty_move_rule_claim_mode_never_automatic() {
+        let mut board_state = BoardState::with_rules(no_repetition_rules());
+
+        shuffle_knights(&mut board_state, SEVENTY_FIVE_MOVE_RULE_LIMIT + 4);
+
+        assert_eq!(board_state.get_game_status(), &GameStatus::Ongoing);
+    }
+
+    #[test]
+    fn fifty_move_rule_automatic_mode() {
+        let mut board_state = BoardState::with_rules(GameRules {
+            fifty_move_mode: FiftyMoveMode::Automatic,
+            repetition_mode: RepetitionMode::Never,
+        });
+
+        shuffle_knights(&mut board_state, SEVENTY_FIVE_MOVE_RULE_LIMIT - 1);
+        assert_eq!(board_state.get_game_status(), &GameStatus::Ongoing);
+
+        board_state.move_piece(knight_shuffle()[(SEVENTY_FIVE_MOVE_RULE_LIMIT - 1) % 4]);
+        assert_eq!(board_state.get_game_status(), &GameStatus::Draw(DrawReason::SeventyFiveMoveRule));
+    }
+
+    #[test]
+    fn fifty_move_rule_claim_after_game_over() {
+        let mut board_state = BoardState::with_rules(no_repetition_rules());
+        shuffle_knights(&mut board_state, FIFTY_MOVE_RULE_LIMIT);
+        board_state.resign(WHITE);
+
+        assert!(!board_state.claim_fifty_move_draw());
+        assert_eq!(board_state.get_game_status(), &GameStatus::BlackWins);
+    }
+
+    #[test]
+    fn move_piece_passes_turn() {
+        let mut board_state = BoardState::new();
+
+        board_state.move_piece(knight_shuffle()[0]);
+
+        assert_eq!(board_state.get_player_turn(), &BLACK);
+    }
+
+    #[test]
+    fn repetition_count_starts_at_one() {
+        let board_state = BoardState::new();
+
+        assert_eq!(board_state.repetition_count(), 1);
+    }
+
+    #[test]
+    fn repetition_count_knight_shuffle() {
+        let mut board_state = BoardState::with_rules(no_repetition_rules());
+
+        shuffle_knights(&mut board_state, 4);
+        assert_eq!(board_state.repetition_count(), 2);
+
+        shuffle_knights(&mut board_state, 1);
+        assert_eq!(board_state.repetition_count(), 2);
+
+        board_state.move_piece(knight_shuffle()[1]);
+        board_state.move_piece(knight_shuffle()[2]);
+        board_state.move_piece(knight_shuffle()[3]);
+        assert_eq!(board_state.repetition_count(), 3);
+        assert_eq!(board_state.get_game_status(), &GameStatus::Ongoing);
+    }
+
+    #[test]
+    fn threefold_repetition_draw() {
+        let mut board_state = BoardState::new();
+
+        shuffle_knights(&mut board_state, 7);
+        assert_eq!(board_state.get_game_status(), &GameStatus::Ongoing);
+
+        board_state.move_piece(knight_shuffle()[3]);
+        assert_eq!(board_state.get_game_status(), &GameStatus::Draw(DrawReason::ThreefoldRepetition));
+    }
+
+    #[test]
+    fn repetition_needs_same_player_turn() {
+        let mut board_state = BoardState::new();
+
+        // the white knight takes three moves to come back, so black is to move the second time
+        let triangle = [
+            StoredMove { start_pos: sq("g1"), end_pos: sq("f3"), promotion: None },
+            StoredMove { start_pos: sq("g8"), end_pos: sq("f6"), promotion: None },
+            StoredMove { start_pos: sq("f3"), end_pos: sq("h4"), promotion: None },
+            StoredMove { start_pos: sq("f6"), end_pos: sq("g8"), promotion: None },
+            StoredMove { start_pos: sq("h4"), end_pos: sq("g1"), promotion: None },
+        ];
+        for stored_move in triangle {
+            board_state.move_piece(stored_move);
+        }
+
+        assert_eq!(board_state.repetition_count(), 1);
+    }
+
+    #[test]
+    fn repetition_needs_same_castling_rights() {
+        let mut board_state = castle_board();
+
+        board_state.move_piece(StoredMove { start_pos: sq("h1"), end_pos: sq("g1"), promotion: None });
+        board_state.move_piece(StoredMove { start_pos: sq("h8"), end_pos: sq("g8"), promotion: None });
+        board_state.move_piece(StoredMove { start_pos: sq("g1"), end_pos: sq("h1"), promotion: None });
+        board_state.move_piece(StoredMove { start_pos: sq("g8"), end_pos: sq("h8"), promotion: None });
+
+        assert_eq!(board_state.repetition_count(), 1);
+    }
+
+    // positions covering castling, en passant, promotions with and without captures, and a king capture
+    const UNDO_FENS: [&str; 5] = [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R b KQkq - 0 1",
+        "n1n1k3/1P6/8/2pP4/8/8/6p1/4K2R w K c6 3 20",
+        "4k3/8/8/8/8/8/1p4q1/R3K2R b KQ - 0 1",
+    ];
+
+    #[test]
+    fn make_then_unmake_every_legal_move() {
+        for fen in UNDO_FENS {
+            let mut board_state = BoardState::from_fen(fen).unwrap();
+            let before = board_state.clone();
+
+            for stored_move in MoveGeneration::gen_moves_for_player(&before, *before.get_player_turn()) {
+                let undo_info = board_state.make_move(stored_move);
+                assert_ne!(board_state, before);
+
+                board_state.unmake_move(undo_info);
+                assert_eq!(board_state, before, "{} in {}", stored_move, fen);
+            }
+        }
+    }
+
+    #[test]
+    fn bitboards_match_tiles_after_every_move() {
+        for fen in UNDO_FENS {
+            let before = BoardState::from_fen(fen).unwrap();
+
+            for stored_move in MoveGeneration::gen_moves_for_player(&before, *before.get_player_turn()) {
+                let mut board_state = before.clone();
+                board_state.move_piece(stored_move);
+
+                assert_eq!(board_state.bitboards, Bitboards::from_tiles(&board_state.mailbox), "{} in {}", stored_move, fen);
+            }
+        }
+    }
+
+    #[test]
+    fn unmake_a_whole_game_in_reverse() {
+        let mut board_state = BoardState::new();
+        let start = board_state.clone();
+        let mut positions = Vec::new();
+        let mut undo_infos = Vec::new();
+
+        // picks a spread of moves without needing a random number generator
+        for ply in 0..80 {
+            let moves = MoveGeneration::gen_moves_for_player(&board_state, *board_state.get_player_turn());
+            if moves.is_empty() {
+                break;
+            }
+            positions.push(board_state.clone());
+            undo_infos.push(board_state.make_move(moves[(ply * 7) % moves.len()]));
+        }
+
+        while let Some(undo_info) = undo_infos.pop() {
+            board_state.unmake_move(undo_info);
+            assert_eq!(board_state, positions.pop().unwrap());
+        }
+        assert_eq!(board_state, start);
+    }
+
+    #[test]
+    fn unmake_king_capture_reopens_game() {
+        let mut board_state = BoardState::from_fen("4k3/8/8/8/8/8/8/4RK2 w - - 0 1").unwrap();
+
+        let undo_info = board_state.make_move(StoredMove { start_pos: sq("e1"), end_pos: sq("e8"), promotion: None });
+        assert_eq!(board_state.get_game_status(), &GameStatus::WhiteWins);
+        assert_eq!(undo_info.captured_piece(), Some(Piece::new(KING, BLACK)));
+
+        board_state.unmake_move(undo_info);
+        assert_eq!(board_state.get_game_status(), &GameStatus::Ongoing);
+        assert_eq!(board_state.get_tile_at_pos(sq("e8")).get_piece(), &Some(Piece::new(KING, BLACK)));
+    }
+
+    #[test]
+    fn unmake_promotion_restores_pawn() {
+        let mut board_state = BoardState::from_fen("n1n1k3/1P6/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        let promotion = StoredMove { start_pos: sq("b7"), end_pos: sq("c8"), promotion: Some(Piece::new(QUEEN, WHITE)) };
+
+        let undo_info = board_state.make_move(promotion);
+        assert_eq!(undo_info.get_moved_piece(), &Piece::new(PAWN, WHITE));
+
+        board_state.unmake_move(undo_info);
+        assert_eq!(board_state.get_tile_at_pos(sq("b7")).get_piece(), &Some(Piece::new(PAWN, WHITE)));
+        assert_eq!(board_state.get_tile_at_pos(sq("c8")).get_piece(), &Some(Piece::new(KNIGHT, BLACK)));
+    }
+}
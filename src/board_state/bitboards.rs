@@ -0,0 +1,162 @@
+use crate::board_state::{ Piece, PieceType, Player, Square, Tile };
+
+// The pieces on the board as one u64 per player and piece type, with bit Square::index() set
+// when that piece is on the square. move generation works on these, while BoardState keeps a
+// mailbox of tiles alongside for reading what is on a single square
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Bitboards {
+    // indexed [player][piece type], in the order the enums are declared
+    pieces: [[u64; 6]; 2],
+}
+
+// every piece type, in the order of piece_type_index
+pub(crate) const PIECE_TYPES: [PieceType; 6] = [
+    PieceType::King,
+    PieceType::Queen,
+    PieceType::Rook,
+    PieceType::Knight,
+    PieceType::Bishop,
+    PieceType::Pawn,
+];
+
+impl Bitboards {
+    pub fn new() -> Bitboards {
+        Bitboards { pieces: [[0; 6]; 2] }
+    }
+
+    pub fn from_tiles(tiles: &[Tile]) -> Bitboards {
+        let mut bitboards = Bitboards::new();
+        for tile in tiles {
+            bitboards.set(*tile.get_pos(), *tile.get_piece());
+        }
+        bitboards
+    }
+
+    pub fn bit(pos: Square) -> u64 {
+        1 << pos.index()
+    }
+
+    // the squares of every set bit, from a1 towards h8
+    pub fn squares(mut bits: u64) -> impl Iterator<Item = Square> {
+        std::iter::from_fn(move || {
+            if bits == 0 {
+                return None;
+            }
+            let index = bits.trailing_zeros() as usize;
+            // clears the lowest set bit
+            bits &= bits - 1;
+            Square::from_index(index)
+        })
+    }
+
+    pub fn get_pieces(&self, piece: Piece) -> u64 {
+        self.pieces[Bitboards::player_index(*piece.get_player())][Bitboards::piece_type_index(*piece.get_piece_type())]
+    }
+
+    pub fn get_player_pieces(&self, player: Player) -> u64 {
+        self.pieces[Bitboards::player_index(player)].iter().fold(0, |bits, pieces| bits | pieces)
+    }
+
+    pub fn get_occupied(&self) -> u64 {
+        self.get_player_pieces(Player::White) | self.get_player_pieces(Player::Black)
+    }
+
+    // BoardState::get_tile_at_pos is the quicker way to read a square off a board
+    pub fn piece_at(&self, pos: Square) -> Option<Piece> {
+        let bit = Bitboards::bit(pos);
+        // finding the player first means only that player's six boards are searched
+        let player = *[Player::White, Player::Black].iter()
+            .find(|player| self.get_player_pieces(**player) & bit != 0)?;
+        let pieces = &self.pieces[Bitboards::player_index(player)];
+
+        PIECE_TYPES.iter()
+            .find(|piece_type| pieces[Bitboards::piece_type_index(**piece_type)] & bit != 0)
+            .map(|piece_type| Piece::new(*piece_type, player))
+    }
+
+    // clears the square, then puts the piece there if there is one
+    pub fn set(&mut self, pos: Square, piece: Option<Piece>) {
+        let bit = Bitboards::bit(pos);
+        for pieces in self.pieces.iter_mut().flatten() {
+            *pieces &= !bit;
+        }
+
+        if let Some(piece) = piece {
+            self.pieces[Bitboards::player_index(*piece.get_player())][Bitboards::piece_type_index(*piece.get_piece_type())] |= bit;
+        }
+    }
+
+    pub(crate) fn player_index(player: Player) -> usize {
+        match player {
+            Player::White => 0,
+            Player::Black => 1,
+        }
+    }
+
+    pub(crate) fn piece_type_index(piece_type: PieceType) -> usize {
+        match piece_type {
+            PieceType::King => 0,
+            PieceType::Queen => 1,
+            PieceType::Rook => 2,
+            PieceType::Knight => 3,
+            PieceType::Bishop => 4,
+            PieceType::Pawn => 5,
+        }
+    }
+}
+
+impl Default for Bitboards {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board_state::square::sq;
+    use crate::board_state::BoardState;
+
+    #[test]
+    fn set_and_piece_at() {
+        let mut bitboards = Bitboards::new();
+        let knight = Piece::new(PieceType::Knight, Player::Black);
+
+        bitboards.set(sq("f6"), Some(knight));
+
+        assert_eq!(bitboards.piece_at(sq("f6")), Some(knight));
+        assert_eq!(bitboards.get_pieces(knight), Bitboards::bit(sq("f6")));
+        assert_eq!(bitboards.get_player_pieces(Player::White), 0);
+    }
+
+    #[test]
+    fn set_replaces_and_clears() {
+        let mut bitboards = Bitboards::new();
+        let pawn = Piece::new(PieceType::Pawn, Player::White);
+        let queen = Piece::new(PieceType::Queen, Player::Black);
+
+        bitboards.set(sq("d5"), Some(pawn));
+        bitboards.set(sq("d5"), Some(queen));
+        assert_eq!(bitboards.piece_at(sq("d5")), Some(queen));
+        assert_eq!(bitboards.get_pieces(pawn), 0);
+
+        bitboards.set(sq("d5"), None);
+        assert_eq!(bitboards.get_occupied(), 0);
+    }
+
+    #[test]
+    fn starting_position_occupancy() {
+        let bitboards = *BoardState::new().get_bitboards();
+
+        assert_eq!(bitboards.get_player_pieces(Player::White), 0xffff);
+        assert_eq!(bitboards.get_player_pieces(Player::Black), 0xffff << 48);
+        assert_eq!(bitboards.get_pieces(Piece::new(PieceType::Rook, Player::White)), Bitboards::bit(sq("a1")) | Bitboards::bit(sq("h1")));
+    }
+
+    #[test]
+    fn squares_in_index_order() {
+        let bits = Bitboards::bit(sq("c3")) | Bitboards::bit(sq("a1")) | Bitboards::bit(sq("h8"));
+
+        assert_eq!(Bitboards::squares(bits).collect::<Vec<Square>>(), vec![sq("a1"), sq("c3"), sq("h8")]);
+    }
+}
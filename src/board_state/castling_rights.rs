@@ -0,0 +1,129 @@
+use crate::board_state::Player;
+
+// Tracks which castles are still allowed for each player
+// a right is lost once the king or the matching rook moves, or the rook is captured
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct CastlingRights {
+    white_kingside: bool,
+    white_queenside: bool,
+    black_kingside: bool,
+    black_queenside: bool,
+}
+
+impl CastlingRights {
+    // at the start of a game, every castle is allowed
+    pub fn new() -> CastlingRights {
+        CastlingRights {
+            white_kingside: true,
+            white_queenside: true,
+            black_kingside: true,
+            black_queenside: true,
+        }
+    }
+
+    pub fn none() -> CastlingRights {
+        CastlingRights {
+            white_kingside: false,
+            white_queenside: false,
+            black_kingside: false,
+            black_queenside: false,
+        }
+    }
+
+    pub fn can_castle_kingside(&self, player: Player) -> bool {
+        match player {
+            Player::White => self.white_kingside,
+            Player::Black => self.black_kingside,
+        }
+    }
+
+    pub fn can_castle_queenside(&self, player: Player) -> bool {
+        match player {
+            Player::White => self.white_queenside,
+            Player::Black => self.black_queenside,
+        }
+    }
+
+    pub fn revoke_kingside(&mut self, player: Player) {
+        match player {
+            Player::White => self.white_kingside = false,
+            Player::Black => self.black_kingside = false,
+        }
+    }
+
+    pub fn revoke_queenside(&mut self, player: Player) {
+        match player {
+            Player::White => self.white_queenside = false,
+            Player::Black => self.black_queenside = false,
+        }
+    }
+
+    pub fn revoke_all(&mut self, player: Player) {
+        self.revoke_kingside(player);
+        self.revoke_queenside(player);
+    }
+}
+
+impl Default for CastlingRights {
+    fn default() -> Self {
+        CastlingRights::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_allows_all() {
+        let rights = CastlingRights::new();
+
+        assert!(rights.can_castle_kingside(Player::White));
+        assert!(rights.can_castle_queenside(Player::White));
+        assert!(rights.can_castle_kingside(Player::Black));
+        assert!(rights.can_castle_queenside(Player::Black));
+    }
+
+    #[test]
+    fn none_allows_nothing() {
+        let rights = CastlingRights::none();
+
+        assert!(!rights.can_castle_kingside(Player::White));
+        assert!(!rights.can_castle_queenside(Player::White));
+        assert!(!rights.can_castle_kingside(Player::Black));
+        assert!(!rights.can_castle_queenside(Player::Black));
+    }
+
+    #[test]
+    fn revoke_kingside_only_affects_one_side() {
+        let mut rights = CastlingRights::new();
+        rights.revoke_kingside(Player::White);
+
+        assert!(!rights.can_castle_kingside(Player::White));
+        assert!(rights.can_castle_queenside(Player::White));
+        assert!(rights.can_castle_kingside(Player::Black));
+    }
+
+    #[test]
+    fn revoke_queenside_only_affects_one_side() {
+        let mut rights = CastlingRights::new();
+        rights.revoke_queenside(Player::Black);
+
+        assert!(!rights.can_castle_queenside(Player::Black));
+        assert!(rights.can_castle_kingside(Player::Black));
+        assert!(rights.can_castle_queenside(Player::White));
+    }
+
+    #[test]
+    fn revoke_all_only_affects_one_player() {
+        let mut rights = CastlingRights::new();
+        rights.revoke_all(Player::White);
+
+        assert_eq!(rights, CastlingRights {
+            white_kingside: false,
+            white_queenside: false,
+            black_kingside: true,
+            black_queenside: true,
+        });
+    }
+}
@@ -0,0 +1,395 @@
+use crate::board_state::{ Bitboards, BoardState, CastlingRights, FenError, GameRules, Piece, PieceType, Player, PlayerView, Square, ViewTile };
+
+// FEN (Forsyth-Edwards Notation) describes a position in one line of six fields -
+// piece placement from rank 8 down, side to move, castling rights, en passant square,
+// halfmove clock and fullmove number
+impl BoardState {
+    pub fn from_fen(fen: &str) -> Result<BoardState, FenError> {
+        BoardState::from_fen_with_rules(fen, GameRules::new())
+    }
+
+    // FEN has no field for the rules, so they are given separately, like BoardState::with_rules
+    pub fn from_fen_with_rules(fen: &str, rules: GameRules) -> Result<BoardState, FenError> {
+        let fields: Vec<&str> = fen.split_whitespace().collect();
+        if fields.len() != 6 {
+            return Err(FenError::WrongFieldCount { expected: 6, found: fields.len() });
+        }
+
+        let mut board_state = BoardState::with_rules(rules);
+        let tiles = placement_from_fen(fields[0], false)?;
+        for pos in Square::all() {
+            if let ViewTile::Visible(piece) = tiles[pos.get_rank()][pos.get_file()] {
+                board_state.set_tile(piece, pos);
+            }
+        }
+
+        board_state.player_turn = player_from_fen(fields[1])
+            .ok_or_else(|| FenError::InvalidSideToMove(String::from(fields[1])))?;
+
+        board_state.castling_rights = BoardState::castling_from_fen(fields[2])?;
+        board_state.en_passant = BoardState::en_passant_from_fen(fields[3], board_state.player_turn)?;
+        // the square is only open to en passant if the pawn that skipped it is still in front of it
+        if let Some(pawn_pos) = board_state.get_en_passant_pawn_pos() {
+            let pawn = Piece::new(PieceType::Pawn, board_state.player_turn.opponent());
+            if board_state.get_bitboards().get_pieces(pawn) & Bitboards::bit(pawn_pos) == 0 {
+                return Err(FenError::InvalidEnPassant(String::from(fields[3])));
+            }
+        }
+
+        board_state.fifty_move_rule_count = fields[4].parse()
+            .map_err(|_| FenError::InvalidHalfmoveClock(String::from(fields[4])))?;
+        board_state.fullmove_number = fields[5].parse().ok()
+            .filter(|number| *number >= 1)
+            .ok_or_else(|| FenError::InvalidFullmoveNumber(String::from(fields[5])))?;
+        board_state.hash_key = board_state.compute_hash_key();
+
+        Ok(board_state)
+    }
+
+    pub fn to_fen(&self) -> String {
+        let mut tiles = [[ViewTile::Hidden; 8]; 8];
+        for tile in self.mailbox.iter() {
+            let pos = tile.get_pos();
+            tiles[pos.get_rank()][pos.get_file()] = ViewTile::Visible(*tile.get_piece());
+        }
+
+        let side_to_move = player_to_fen(self.player_turn);
+
+        let mut castling = String::new();
+        let rights = [
+            (self.castling_rights.can_castle_kingside(Player::White), 'K'),
+            (self.castling_rights.can_castle_queenside(Player::White), 'Q'),
+            (self.castling_rights.can_castle_kingside(Player::Black), 'k'),
+            (self.castling_rights.can_castle_queenside(Player::Black), 'q'),
+        ];
+        for (allowed, symbol) in rights {
+            if allowed {
+                castling.push(symbol);
+            }
+        }
+        if castling.is_empty() {
+            castling.push('-');
+        }
+
+        let en_passant = match self.en_passant {
+            Some(pos) => pos.to_string(),
+            None => String::from("-"),
+        };
+
+        format!(
+            "{} {} {} {} {} {}",
+            placement_to_fen(&tiles),
+            side_to_move,
+            castling,
+            en_passant,
+            self.fifty_move_rule_count,
+            self.fullmove_number
+        )
+    }
+
+    // the fog of war version of FEN, see PlayerView::to_fog_fen
+    pub fn to_fog_fen(&self, player: Player) -> String {
+        PlayerView::new(self, player).to_fog_fen()
+    }
+
+    // "-" for no castles, otherwise each of KQkq at most once
+    fn castling_from_fen(field: &str) -> Result<CastlingRights, FenError> {
+        if field == "-" {
+            return Ok(CastlingRights::none());
+        }
+
+        let mut seen = Vec::new();
+        for symbol in field.chars() {
+            if seen.contains(&symbol) {
+                return Err(FenError::InvalidCastling(String::from(field)));
+            }
+            seen.push(symbol);
+        }
+
+        let mut castling_rights = CastlingRights::new();
+        for (symbol, player, kingside) in [
+            ('K', Player::White, true),
+            ('Q', Player::White, false),
+            ('k', Player::Black, true),
+            ('q', Player::Black, false),
+        ] {
+            if let Some(index) = seen.iter().position(|seen_symbol| *seen_symbol == symbol) {
+                seen.remove(index);
+            } else if kingside {
+                castling_rights.revoke_kingside(player);
+            } else {
+                castling_rights.revoke_queenside(player);
+            }
+        }
+
+        // anything left over is not one of KQkq
+        if !seen.is_empty() {
+            return Err(FenError::InvalidCastling(String::from(field)));
+        }
+
+        Ok(castling_rights)
+    }
+
+    // the en passant square is the one skipped by the last move, so it has to be on
+    // rank 3 when black is to move and on rank 6 when white is to move
+    fn en_passant_from_fen(field: &str, player_turn: Player) -> Result<Option<Square>, FenError> {
+        if field == "-" {
+            return Ok(None);
+        }
+
+        let skipped_rank = match player_turn {
+            Player::White => 5,
+            Player::Black => 2,
+        };
+        match Square::from_algebraic(field) {
+            Some(pos) if pos.get_rank() == skipped_rank => Ok(Some(pos)),
+            _ => Err(FenError::InvalidEnPassant(String::from(field))),
+        }
+    }
+}
+
+// the piece placement field, from rank 8 down with ranks separated by '/'
+// a digit counts empty squares, and when allow_hidden is set '?' marks a single hidden square
+pub(crate) fn placement_from_fen(field: &str, allow_hidden: bool) -> Result<[[ViewTile; 8]; 8], FenError> {
+    let mut tiles = [[ViewTile::Hidden; 8]; 8];
+
+    let ranks: Vec<&str> = field.split('/').collect();
+    if ranks.len() != 8 {
+        return Err(FenError::WrongRankCount(ranks.len()));
+    }
+    for (index, rank_str) in ranks.iter().enumerate() {
+        // the placement starts from rank 8
+        let rank = 7 - index;
+        let mut file = 0;
+
+        for symbol in rank_str.chars() {
+            let (view_tile, count) = match symbol.to_digit(10) {
+                Some(empty) if (1..=8).contains(&empty) => (ViewTile::Visible(None), empty as usize),
+                _ if symbol == '?' && allow_hidden => (ViewTile::Hidden, 1),
+                _ => {
+                    let piece = Piece::from_fen_symbol(symbol).ok_or(FenError::InvalidPiece(symbol))?;
+                    (ViewTile::Visible(Some(piece)), 1)
+                },
+            };
+
+            for _ in 0..count {
+                if file >= 8 {
+                    return Err(FenError::WrongRankLength(rank + 1));
+                }
+                tiles[rank][file] = view_tile;
+                file += 1;
+            }
+        }
+
+        if file != 8 {
+            return Err(FenError::WrongRankLength(rank + 1));
+        }
+    }
+
+    Ok(tiles)
+}
+
+pub(crate) fn placement_to_fen(tiles: &[[ViewTile; 8]; 8]) -> String {
+    let mut ranks = Vec::new();
+    for row in tiles.iter().rev() {
+        let mut rank_str = String::new();
+        let mut empty = 0;
+
+        for view_tile in row.iter() {
+            if view_tile == &ViewTile::Visible(None) {
+                empty += 1;
+                continue;
+            }
+            if empty > 0 {
+                rank_str.push_str(&empty.to_string());
+                empty = 0;
+            }
+            match view_tile {
+                ViewTile::Visible(Some(piece)) => rank_str.push(piece.fen_symbol()),
+                _ => rank_str.push('?'),
+            }
+        }
+        if empty > 0 {
+            rank_str.push_str(&empty.to_string());
+        }
+
+        ranks.push(rank_str);
+    }
+
+    ranks.join("/")
+}
+
+pub(crate) fn player_to_fen(player: Player) -> &'static str {
+    match player {
+        Player::White => "w",
+        Player::Black => "b",
+    }
+}
+
+pub(crate) fn player_from_fen(field: &str) -> Option<Player> {
+    match field {
+        "w" => Some(Player::White),
+        "b" => Some(Player::Black),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board_state::square::sq;
+    use crate::board_state::{ PieceType, StoredMove };
+
+    const START_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+    #[test]
+    fn to_fen_starting_position() {
+        assert_eq!(BoardState::new().to_fen(), START_FEN);
+    }
+
+    #[test]
+    fn from_fen_starting_position() {
+        let board_state = BoardState::from_fen(START_FEN).unwrap();
+
+        assert_eq!(board_state.hash_key(), BoardState::new().hash_key());
+        assert_eq!(board_state.get_fullmove_number(), 1);
+    }
+
+    #[test]
+    fn round_trip_after_moves() {
+        let mut board_state = BoardState::new();
+        board_state.move_piece(StoredMove { start_pos: sq("e2"), end_pos: sq("e4"), promotion: None });
+
+        assert_eq!(
+            board_state.to_fen(),
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1"
+        );
+
+        board_state.move_piece(StoredMove { start_pos: sq("g8"), end_pos: sq("f6"), promotion: None });
+
+        assert_eq!(
+            board_state.to_fen(),
+            "rnbqkb1r/pppppppp/5n2/8/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 1 2"
+        );
+    }
+
+    #[test]
+    fn round_trip_custom_position() {
+        let fen = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w Kq - 12 40";
+
+        assert_eq!(BoardState::from_fen(fen).unwrap().to_fen(), fen);
+    }
+
+    #[test]
+    fn to_fog_fen_matches_player_view() {
+        let board_state = BoardState::new();
+
+        assert_eq!(
+            board_state.to_fog_fen(Player::Black),
+            PlayerView::new(&board_state, Player::Black).to_fog_fen()
+        );
+    }
+
+    #[test]
+    fn from_fen_reads_every_field() {
+        let board_state = BoardState::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 7 33").unwrap();
+
+        assert_eq!(board_state.get_player_turn(), &Player::White);
+        assert_eq!(board_state.get_castling_rights(), &CastlingRights::none());
+        assert_eq!(board_state.get_en_passant(), &Some(sq("d6")));
+        assert_eq!(board_state.get_fifty_move_rule_count(), 7);
+        assert_eq!(board_state.get_fullmove_number(), 33);
+        assert_eq!(
+            board_state.get_tile_at_pos(sq("d5")).get_piece(),
+            &Some(Piece::new(PieceType::Pawn, Player::Black))
+        );
+    }
+
+    #[test]
+    fn from_fen_partial_castling() {
+        let board_state = BoardState::from_fen("r3k2r/8/8/8/8/8/8/R3K2R b Qk - 0 1").unwrap();
+        let castling_rights = board_state.get_castling_rights();
+
+        assert!(!castling_rights.can_castle_kingside(Player::White));
+        assert!(castling_rights.can_castle_queenside(Player::White));
+        assert!(castling_rights.can_castle_kingside(Player::Black));
+        assert!(!castling_rights.can_castle_queenside(Player::Black));
+    }
+
+    #[test]
+    fn from_fen_wrong_field_count() {
+        let result = BoardState::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq -");
+
+        assert_eq!(result.unwrap_err(), FenError::WrongFieldCount { expected: 6, found: 4 });
+    }
+
+    #[test]
+    fn from_fen_wrong_rank_count() {
+        let result = BoardState::from_fen("rnbqkbnr/pppppppp/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+
+        assert_eq!(result.unwrap_err(), FenError::WrongRankCount(7));
+    }
+
+    #[test]
+    fn from_fen_rank_too_short_and_too_long() {
+        let short = BoardState::from_fen("rnbqkbnr/ppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+        let long = BoardState::from_fen("rnbqkbnr/pppppppp/8/8/8/44P/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+
+        assert_eq!(short.unwrap_err(), FenError::WrongRankLength(7));
+        assert_eq!(long.unwrap_err(), FenError::WrongRankLength(3));
+    }
+
+    #[test]
+    fn from_fen_invalid_piece() {
+        let result = BoardState::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNX w KQkq - 0 1");
+
+        assert_eq!(result.unwrap_err(), FenError::InvalidPiece('X'));
+    }
+
+    #[test]
+    fn from_fen_invalid_side_to_move() {
+        let result = BoardState::from_fen("8/8/8/8/8/8/8/8 white - - 0 1");
+
+        assert_eq!(result.unwrap_err(), FenError::InvalidSideToMove(String::from("white")));
+    }
+
+    #[test]
+    fn from_fen_invalid_castling() {
+        for field in ["KK", "KQx", ""] {
+            let fen = format!("8/8/8/8/8/8/8/8 w {} - 0 1", field);
+            let result = BoardState::from_fen(&fen);
+
+            // an empty field collapses into the whitespace, leaving too few fields
+            if field.is_empty() {
+                assert_eq!(result.unwrap_err(), FenError::WrongFieldCount { expected: 6, found: 5 });
+            } else {
+                assert_eq!(result.unwrap_err(), FenError::InvalidCastling(String::from(field)));
+            }
+        }
+    }
+
+    #[test]
+    fn from_fen_en_passant_on_wrong_rank() {
+        let result = BoardState::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d3 0 1");
+
+        assert_eq!(result.unwrap_err(), FenError::InvalidEnPassant(String::from("d3")));
+    }
+
+    #[test]
+    fn from_fen_en_passant_without_pawn() {
+        let empty = BoardState::from_fen("4k3/8/8/4P3/8/8/8/4K3 w - d6 0 1");
+        let knight = BoardState::from_fen("4k3/8/8/3nP3/8/8/8/4K3 w - d6 0 1");
+
+        assert_eq!(empty.unwrap_err(), FenError::InvalidEnPassant(String::from("d6")));
+        assert_eq!(knight.unwrap_err(), FenError::InvalidEnPassant(String::from("d6")));
+    }
+
+    #[test]
+    fn from_fen_invalid_clocks() {
+        let halfmove = BoardState::from_fen("8/8/8/8/8/8/8/8 w - - -1 1");
+        let fullmove = BoardState::from_fen("8/8/8/8/8/8/8/8 w - - 0 0");
+
+        assert_eq!(halfmove.unwrap_err(), FenError::InvalidHalfmoveClock(String::from("-1")));
+        assert_eq!(fullmove.unwrap_err(), FenError::InvalidFullmoveNumber(String::from("0")));
+    }
+}
@@ -0,0 +1,56 @@
+use std::fmt;
+
+// The reasons BoardState::from_fen or PlayerView::from_fog_fen can refuse a string
+#[derive(Debug, Clone, PartialEq)]
+pub enum FenError {
+    // the fields are separated by spaces, six for FEN and three for fog FEN
+    WrongFieldCount { expected: usize, found: usize },
+    // the placement field has eight ranks separated by '/'
+    WrongRankCount(usize),
+    // a rank (1 to 8) whose pieces and empty counts do not add up to eight files
+    WrongRankLength(usize),
+    InvalidPiece(char),
+    InvalidSideToMove(String),
+    InvalidViewer(String),
+    InvalidCastling(String),
+    InvalidEnPassant(String),
+    InvalidHalfmoveClock(String),
+    InvalidFullmoveNumber(String),
+}
+
+impl fmt::Display for FenError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FenError::WrongFieldCount { expected, found } => write!(f, "expected {} fields, found {}", expected, found),
+            FenError::WrongRankCount(count) => write!(f, "expected 8 ranks in the piece placement, found {}", count),
+            FenError::WrongRankLength(rank) => write!(f, "rank {} does not cover exactly 8 files", rank),
+            FenError::InvalidPiece(symbol) => write!(f, "'{}' is not a piece or empty square count", symbol),
+            FenError::InvalidSideToMove(field) => write!(f, "side to move must be 'w' or 'b', found '{}'", field),
+            FenError::InvalidViewer(field) => write!(f, "viewer must be 'w' or 'b', found '{}'", field),
+            FenError::InvalidCastling(field) => write!(f, "'{}' is not a valid castling availability", field),
+            FenError::InvalidEnPassant(field) => write!(f, "'{}' is not a valid en passant square", field),
+            FenError::InvalidHalfmoveClock(field) => write!(f, "'{}' is not a valid halfmove clock", field),
+            FenError::InvalidFullmoveNumber(field) => write!(f, "'{}' is not a valid fullmove number", field),
+        }
+    }
+}
+
+impl std::error::Error for FenError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_wrong_rank_length() {
+        assert_eq!(FenError::WrongRankLength(3).to_string(), "rank 3 does not cover exactly 8 files");
+    }
+
+    #[test]
+    fn display_invalid_side_to_move() {
+        assert_eq!(
+            FenError::InvalidSideToMove(String::from("x")).to_string(),
+            "side to move must be 'w' or 'b', found 'x'"
+        );
+    }
+}
@@ -0,0 +1,55 @@
+// How the fifty move rule ends a game
+// in both modes, a player may claim a draw once fifty moves each have passed without
+// a pawn move or capture. the automatic mode also ends the game itself after seventy five
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum FiftyMoveMode {
+    Claim,
+    Automatic,
+}
+
+// How repeated positions are judged
+// since neither player sees the whole board, a player may not know a position has repeated.
+// TruePosition judges repetition on the full board anyway and draws the game on the third
+// occurrence, while Never ignores repetition entirely
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum RepetitionMode {
+    TruePosition,
+    Never,
+}
+
+// Settings that can differ from game to game
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct GameRules {
+    pub fifty_move_mode: FiftyMoveMode,
+    pub repetition_mode: RepetitionMode,
+}
+
+impl GameRules {
+    pub fn new() -> GameRules {
+        GameRules {
+            fifty_move_mode: FiftyMoveMode::Claim,
+            repetition_mode: RepetitionMode::TruePosition,
+        }
+    }
+}
+
+impl Default for GameRules {
+    fn default() -> Self {
+        GameRules::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_uses_claim() {
+        assert_eq!(GameRules::new().fifty_move_mode, FiftyMoveMode::Claim);
+    }
+
+    #[test]
+    fn new_uses_true_position() {
+        assert_eq!(GameRules::new().repetition_mode, RepetitionMode::TruePosition);
+    }
+}
@@ -0,0 +1,57 @@
+use crate::board_state::Player;
+
+// Why a game ended in a draw
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum DrawReason {
+    FiftyMoveRule,
+    SeventyFiveMoveRule,
+    ThreefoldRepetition,
+}
+
+// Where a game is at. There is no checkmate in dark chess, since players are allowed
+// to move into check, so a game is won by capturing the king or by the other player resigning
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum GameStatus {
+    Ongoing,
+    WhiteWins,
+    BlackWins,
+    Draw(DrawReason),
+}
+
+impl GameStatus {
+    pub fn win_for(player: Player) -> GameStatus {
+        match player {
+            Player::White => GameStatus::WhiteWins,
+            Player::Black => GameStatus::BlackWins,
+        }
+    }
+
+    pub fn is_over(&self) -> bool {
+        self != &GameStatus::Ongoing
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn win_for_white() {
+        assert_eq!(GameStatus::win_for(Player::White), GameStatus::WhiteWins);
+    }
+
+    #[test]
+    fn win_for_black() {
+        assert_eq!(GameStatus::win_for(Player::Black), GameStatus::BlackWins);
+    }
+
+    #[test]
+    fn ongoing_is_not_over() {
+        assert!(!GameStatus::Ongoing.is_over());
+    }
+
+    #[test]
+    fn draw_is_over() {
+        assert!(GameStatus::Draw(DrawReason::FiftyMoveRule).is_over());
+    }
+}
@@ -0,0 +1,41 @@
+use std::fmt;
+
+// The reasons BoardState::try_move can refuse a move
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum MoveError {
+    EmptyStart,
+    NotYourPiece,
+    NotYourTurn,
+    IllegalDestination,
+    GameOver,
+}
+
+impl fmt::Display for MoveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let message = match self {
+            MoveError::EmptyStart => "there is no piece on the starting tile",
+            MoveError::NotYourPiece => "the piece on the starting tile belongs to the other player",
+            MoveError::NotYourTurn => "it is the other player's turn",
+            MoveError::IllegalDestination => "the piece cannot move to the given tile",
+            MoveError::GameOver => "the game is already over",
+        };
+        write!(f, "{}", message)
+    }
+}
+
+impl std::error::Error for MoveError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_not_your_turn() {
+        assert_eq!(MoveError::NotYourTurn.to_string(), "it is the other player's turn");
+    }
+
+    #[test]
+    fn display_game_over() {
+        assert_eq!(MoveError::GameOver.to_string(), "the game is already over");
+    }
+}
@@ -0,0 +1,50 @@
+use crate::board_state::{ Piece, Player, Square, StoredMove };
+
+// What happened when a move was applied through BoardState::try_move
+// Like StoredMove, this is only ever created and read, so the data is public
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct MoveRecord {
+    pub stored_move: StoredMove,
+    pub player: Player,
+    pub moved_piece: Piece,
+    // the captured piece and the tile it was taken from, which differs from
+    // the end of the move only for en passant
+    pub captured: Option<(Piece, Square)>,
+}
+
+impl MoveRecord {
+    pub fn captured_piece(&self) -> Option<Piece> {
+        self.captured.map(|(piece, _)| piece)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board_state::square::sq;
+    use crate::board_state::PieceType;
+
+    #[test]
+    fn captured_piece_none() {
+        let record = MoveRecord {
+            stored_move: StoredMove { start_pos: sq("e2"), end_pos: sq("e4"), promotion: None },
+            player: Player::White,
+            moved_piece: Piece::new(PieceType::Pawn, Player::White),
+            captured: None,
+        };
+
+        assert_eq!(record.captured_piece(), None);
+    }
+
+    #[test]
+    fn captured_piece_some() {
+        let record = MoveRecord {
+            stored_move: StoredMove { start_pos: sq("e5"), end_pos: sq("f6"), promotion: None },
+            player: Player::White,
+            moved_piece: Piece::new(PieceType::Pawn, Player::White),
+            captured: Some((Piece::new(PieceType::Pawn, Player::Black), sq("f5"))),
+        };
+
+        assert_eq!(record.captured_piece(), Some(Piece::new(PieceType::Pawn, Player::Black)));
+    }
+}
@@ -38,7 +38,7 @@ impl Piece {
     // these are the utf-8 values for each piece harcoded
     pub fn symbol_utf(&self) -> char {
         if self.player == Player::White {
-            return match self.piece_type {
+            match self.piece_type {
                 PieceType::King => '\u{2654}',
                 PieceType::Queen => '\u{2655}',
                 PieceType::Rook => '\u{2656}',
@@ -48,7 +48,7 @@ impl Piece {
             }
         }
         else {
-            return match self.piece_type {
+            match self.piece_type {
                 PieceType::King => '\u{265A}',
                 PieceType::Queen => '\u{265B}',
                 PieceType::Rook => '\u{265C}',
@@ -59,6 +59,38 @@ impl Piece {
         }
     }
 
+    // the letter used for the piece in FEN, upper case for white and lower case for black
+    pub fn fen_symbol(&self) -> char {
+        let letter = match self.piece_type {
+            PieceType::King => 'k',
+            PieceType::Queen => 'q',
+            PieceType::Rook => 'r',
+            PieceType::Knight => 'n',
+            PieceType::Bishop => 'b',
+            PieceType::Pawn => 'p',
+        };
+
+        match self.player {
+            Player::White => letter.to_ascii_uppercase(),
+            Player::Black => letter,
+        }
+    }
+
+    pub fn from_fen_symbol(symbol: char) -> Option<Piece> {
+        let piece_type = match symbol.to_ascii_lowercase() {
+            'k' => PieceType::King,
+            'q' => PieceType::Queen,
+            'r' => PieceType::Rook,
+            'n' => PieceType::Knight,
+            'b' => PieceType::Bishop,
+            'p' => PieceType::Pawn,
+            _ => return None,
+        };
+        let player = if symbol.is_ascii_uppercase() { Player::White } else { Player::Black };
+
+        Some(Piece::new(piece_type, player))
+    }
+
     pub fn get_piece_type(&self) -> &PieceType {
         &self.piece_type
     }
@@ -145,4 +177,23 @@ mod tests {
 
         assert_eq!(piece.symbol_utf(), '\u{265A}');
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn fen_symbol_by_player() {
+        assert_eq!(Piece::new(PieceType::Knight, Player::White).fen_symbol(), 'N');
+        assert_eq!(Piece::new(PieceType::Pawn, Player::Black).fen_symbol(), 'p');
+    }
+
+    #[test]
+    fn from_fen_symbol_round_trip() {
+        for symbol in "KQRNBPkqrnbp".chars() {
+            assert_eq!(Piece::from_fen_symbol(symbol).unwrap().fen_symbol(), symbol);
+        }
+    }
+
+    #[test]
+    fn from_fen_symbol_invalid() {
+        assert_eq!(Piece::from_fen_symbol('x'), None);
+        assert_eq!(Piece::from_fen_symbol('1'), None);
+    }
+}
@@ -4,6 +4,36 @@ pub enum Player {
     Black
 }
 
+impl Player {
+    pub fn opponent(&self) -> Player {
+        match self {
+            Player::White => Player::Black,
+            Player::Black => Player::White,
+        }
+    }
+
+    // the way the player's pawns move, as a change in rank
+    pub fn forward(&self) -> i32 {
+        match self {
+            Player::White => 1,
+            Player::Black => -1,
+        }
+    }
+
+    // the rank the player's pieces start on, counting from 0
+    pub fn back_rank(&self) -> usize {
+        match self {
+            Player::White => 0,
+            Player::Black => 7,
+        }
+    }
+
+    // the rank the player's pawns start on, one forward of the back rank
+    pub fn pawn_rank(&self) -> usize {
+        (self.back_rank() as i32 + self.forward()) as usize
+    }
+}
+
 // these tests are pretty trivial
 #[cfg(test)]
 mod tests {
@@ -18,4 +48,22 @@ mod tests {
     fn not_equal() {
         assert_ne!(Player::White, Player::Black);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn opponent() {
+        assert_eq!(Player::White.opponent(), Player::Black);
+        assert_eq!(Player::Black.opponent(), Player::White);
+    }
+
+    #[test]
+    fn forward() {
+        assert_eq!(Player::White.forward(), 1);
+        assert_eq!(Player::Black.forward(), -1);
+    }
+
+    #[test]
+    fn back_and_pawn_ranks() {
+        assert_eq!((Player::White.back_rank(), Player::White.pawn_rank()), (0, 1));
+        assert_eq!((Player::Black.back_rank(), Player::Black.pawn_rank()), (7, 6));
+    }
+}
@@ -0,0 +1,286 @@
+use crate::board_state::{ BoardState, FenError, Piece, Player, Square };
+use crate::board_state::fen::{ placement_from_fen, placement_to_fen, player_from_fen, player_to_fen };
+
+// What a single tile looks like to one player
+// a hidden tile carries no information about what is on it, on purpose
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ViewTile {
+    Visible(Option<Piece>),
+    Hidden,
+}
+
+impl ViewTile {
+    pub fn symbol(&self) -> char {
+        match self {
+            ViewTile::Visible(Some(piece)) => piece.symbol(),
+            ViewTile::Visible(None) => '.',
+            ViewTile::Hidden => '?',
+        }
+    }
+
+    pub fn symbol_utf(&self) -> char {
+        match self {
+            ViewTile::Visible(Some(piece)) => piece.symbol_utf(),
+            ViewTile::Visible(None) => '.',
+            ViewTile::Hidden => '\u{2591}',
+        }
+    }
+}
+
+// The board as one player sees it under fog of war
+// this is the only thing that should ever be sent to a player, since it is
+// built from the visibility of the board and cannot hold pieces the player cannot see
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlayerView {
+    player: Player,
+    player_turn: Player,
+    // indexed [rank][file], like the board
+    tiles: [[ViewTile; 8]; 8],
+}
+
+impl PlayerView {
+    pub fn new(board_state: &BoardState, player: Player) -> PlayerView {
+        let visible = board_state.visible_tiles(player);
+        let mut tiles = [[ViewTile::Hidden; 8]; 8];
+
+        for pos in Square::all() {
+            let (file, rank) = (pos.get_file(), pos.get_rank());
+            if visible[rank][file] {
+                tiles[rank][file] = ViewTile::Visible(*board_state.get_tile_at_pos(pos).get_piece());
+            }
+        }
+
+        PlayerView {
+            player,
+            player_turn: *board_state.get_player_turn(),
+            tiles,
+        }
+    }
+
+    pub fn get_player(&self) -> &Player {
+        &self.player
+    }
+
+    pub fn get_player_turn(&self) -> &Player {
+        &self.player_turn
+    }
+
+    pub fn get_tile_at_pos(&self, pos: Square) -> &ViewTile {
+        &self.tiles[pos.get_rank()][pos.get_file()]
+    }
+
+    // fog FEN is the FEN piece placement with '?' for every hidden square, followed by the
+    // side to move and the player the view belongs to, e.g. for white at the start -
+    // "????????/????????/????????/????????/8/8/PPPPPPPP/RNBQKBNR w w"
+    pub fn to_fog_fen(&self) -> String {
+        format!(
+            "{} {} {}",
+            placement_to_fen(&self.tiles),
+            player_to_fen(self.player_turn),
+            player_to_fen(self.player)
+        )
+    }
+
+    pub fn from_fog_fen(fog_fen: &str) -> Result<PlayerView, FenError> {
+        let fields: Vec<&str> = fog_fen.split_whitespace().collect();
+        if fields.len() != 3 {
+            return Err(FenError::WrongFieldCount { expected: 3, found: fields.len() });
+        }
+
+        let tiles = placement_from_fen(fields[0], true)?;
+        let player_turn = player_from_fen(fields[1])
+            .ok_or_else(|| FenError::InvalidSideToMove(String::from(fields[1])))?;
+        let player = player_from_fen(fields[2])
+            .ok_or_else(|| FenError::InvalidViewer(String::from(fields[2])))?;
+
+        Ok(PlayerView { player, player_turn, tiles })
+    }
+
+    pub fn display(&self) -> String {
+        self.display_with(ViewTile::symbol)
+    }
+
+    pub fn display_utf(&self) -> String {
+        self.display_with(ViewTile::symbol_utf)
+    }
+
+    fn display_with(&self, symbol: fn(&ViewTile) -> char) -> String {
+        let mut result = String::from("");
+
+        // rank 8 is printed first, the same as BoardState::display_full_board
+        for row in self.tiles.iter().rev() {
+            for view_tile in row.iter() {
+                result.push(symbol(view_tile));
+                result.push(' ');
+            }
+            result.pop(); // To remove extra space
+            result.push('\n');
+        }
+        result.pop(); // To remove extra new line
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board_state::square::sq;
+    use crate::board_state::{ PieceType, StoredMove };
+
+    #[test]
+    fn view_tile_symbols() {
+        let piece = Piece::new(PieceType::King, Player::White);
+
+        assert_eq!(ViewTile::Visible(Some(piece)).symbol(), 'K');
+        assert_eq!(ViewTile::Visible(None).symbol(), '.');
+        assert_eq!(ViewTile::Hidden.symbol(), '?');
+    }
+
+    #[test]
+    fn view_tile_symbols_utf() {
+        let piece = Piece::new(PieceType::King, Player::Black);
+
+        assert_eq!(ViewTile::Visible(Some(piece)).symbol_utf(), '\u{265A}');
+        assert_eq!(ViewTile::Visible(None).symbol_utf(), '.');
+        assert_eq!(ViewTile::Hidden.symbol_utf(), '\u{2591}');
+    }
+
+    #[test]
+    fn new_hides_enemy_pieces() {
+        let board_state = BoardState::new();
+
+        let view = PlayerView::new(&board_state, Player::White);
+
+        assert_eq!(view.get_tile_at_pos(sq("e8")), &ViewTile::Hidden);
+        assert_eq!(view.get_tile_at_pos(sq("e7")), &ViewTile::Hidden);
+        assert_eq!(view.get_tile_at_pos(sq("e4")), &ViewTile::Visible(None));
+        assert_eq!(
+            view.get_tile_at_pos(sq("e1")),
+            &ViewTile::Visible(Some(Piece::new(PieceType::King, Player::White)))
+        );
+    }
+
+    #[test]
+    fn new_keeps_player_and_turn() {
+        let board_state = BoardState::new();
+
+        let view = PlayerView::new(&board_state, Player::Black);
+
+        assert_eq!(view.get_player(), &Player::Black);
+        assert_eq!(view.get_player_turn(), &Player::White);
+    }
+
+    #[test]
+    fn display_starting_position() {
+        let board_state = BoardState::new();
+
+        let view = PlayerView::new(&board_state, Player::White);
+
+        assert_eq!(view.display(), "\
+? ? ? ? ? ? ? ?
+? ? ? ? ? ? ? ?
+? ? ? ? ? ? ? ?
+? ? ? ? ? ? ? ?
+. . . . . . . .
+. . . . . . . .
+p p p p p p p p
+R N B Q K B N R");
+    }
+
+    #[test]
+    fn display_utf_starting_position_black() {
+        let board_state = BoardState::new();
+
+        let view = PlayerView::new(&board_state, Player::Black);
+        let display = view.display_utf();
+        let rows: Vec<&str> = display.lines().collect();
+
+        assert_eq!(rows[1], "\u{265F} \u{265F} \u{265F} \u{265F} \u{265F} \u{265F} \u{265F} \u{265F}");
+        assert_eq!(rows[3], ". . . . . . . .");
+        assert_eq!(rows[4], "\u{2591} \u{2591} \u{2591} \u{2591} \u{2591} \u{2591} \u{2591} \u{2591}");
+    }
+
+    #[test]
+    fn to_fog_fen_starting_position() {
+        let board_state = BoardState::new();
+
+        assert_eq!(
+            PlayerView::new(&board_state, Player::White).to_fog_fen(),
+            "????????/????????/????????/????????/8/8/PPPPPPPP/RNBQKBNR w w"
+        );
+        assert_eq!(
+            PlayerView::new(&board_state, Player::Black).to_fog_fen(),
+            "rnbqkbnr/pppppppp/8/8/????????/????????/????????/???????? w b"
+        );
+    }
+
+    #[test]
+    fn to_fog_fen_shows_seen_enemy() {
+        let board_state = BoardState::from_fen("4k3/8/8/8/8/8/3p4/4K3 w - - 0 1").unwrap();
+
+        assert_eq!(
+            PlayerView::new(&board_state, Player::White).to_fog_fen(),
+            "????????/????????/????????/????????/????????/????????/???p2??/???1K1?? w w"
+        );
+    }
+
+    #[test]
+    fn to_fog_fen_after_resigning() {
+        let mut board_state = BoardState::new();
+        board_state.resign(Player::White);
+
+        assert_eq!(
+            PlayerView::new(&board_state, Player::White).to_fog_fen(),
+            "????????/????????/????????/????????/8/8/PPPPPPPP/RNBQKBNR w w"
+        );
+    }
+
+    #[test]
+    fn fog_fen_round_trips_every_ply() {
+        let mut board_state = BoardState::new();
+        let moves = [("e2", "e4"), ("d7", "d5"), ("e4", "d5"), ("d8", "d5"), ("b1", "c3"), ("d5", "a5")];
+
+        for (start, end) in moves.iter() {
+            for player in [Player::White, Player::Black] {
+                let view = PlayerView::new(&board_state, player);
+                let parsed = PlayerView::from_fog_fen(&view.to_fog_fen()).unwrap();
+                let visible = board_state.visible_tiles(player);
+
+                assert_eq!(parsed, view);
+                for pos in Square::all() {
+                    let hidden = parsed.get_tile_at_pos(pos) == &ViewTile::Hidden;
+                    assert_eq!(hidden, !visible[pos.get_rank()][pos.get_file()]);
+                }
+            }
+
+            let planned_move = StoredMove { start_pos: sq(start), end_pos: sq(end), promotion: None };
+            board_state.try_move(*board_state.get_player_turn(), planned_move).unwrap();
+        }
+    }
+
+    #[test]
+    fn from_fog_fen_errors() {
+        let placement = "????????/????????/????????/????????/8/8/PPPPPPPP/RNBQKBNR";
+
+        assert_eq!(
+            PlayerView::from_fog_fen(placement).unwrap_err(),
+            FenError::WrongFieldCount { expected: 3, found: 1 }
+        );
+        assert_eq!(
+            PlayerView::from_fog_fen(&format!("{} w x", placement)).unwrap_err(),
+            FenError::InvalidViewer(String::from("x"))
+        );
+        assert_eq!(
+            PlayerView::from_fog_fen("???????/8/8/8/8/8/8/8 w w").unwrap_err(),
+            FenError::WrongRankLength(8)
+        );
+    }
+
+    #[test]
+    fn full_fen_rejects_hidden_squares() {
+        let result = BoardState::from_fen("????????/8/8/8/8/8/8/4K3 w - - 0 1");
+
+        assert_eq!(result.unwrap_err(), FenError::InvalidPiece('?'));
+    }
+}
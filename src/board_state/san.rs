@@ -0,0 +1,332 @@
+use crate::board_state::{ BoardState, Piece, PieceType, SanError, Square, StoredMove };
+use crate::move_generation::MoveGeneration;
+
+// Standard Algebraic Notation, such as "Nf3", "exd5", "O-O" or "e8=Q"
+// check markers: since there is no check or checkmate in dark chess (kings are captured, and moving
+// into check is allowed), "+" and "#" are never written. they are still accepted and ignored when
+// reading, along with annotations such as "!" and "?", so SAN from ordinary chess tools can be read
+impl StoredMove {
+    // the move as SAN in the position it is played from
+    // a piece is disambiguated by file if that is enough, then by rank, and only then by both,
+    // against every legal move from MoveGeneration
+    // panics if there is no piece on the starting tile, since the move could not be played
+    pub fn to_san(&self, board_state: &BoardState) -> String {
+        let start = self.start_pos;
+        let end = self.end_pos;
+        let piece = board_state.get_tile_at_pos(start).get_piece().unwrap();
+
+        if is_castle(board_state, self) {
+            return String::from(if end.get_file() == 6 { "O-O" } else { "O-O-O" });
+        }
+
+        let is_pawn = piece.get_piece_type() == &PieceType::Pawn;
+        let captures = board_state.get_tile_at_pos(end).get_piece().is_some()
+            || (is_pawn && Some(end) == *board_state.get_en_passant());
+        let start_name = start.to_string();
+
+        let mut san = String::new();
+        if is_pawn {
+            if captures {
+                san.push_str(&start_name[..1]);
+            }
+        } else {
+            san.push(piece.symbol());
+
+            let others: Vec<Square> = MoveGeneration::gen_moves_for_player(board_state, *piece.get_player())
+                .into_iter()
+                .filter(|other| other.end_pos == end && other.start_pos != start)
+                .map(|other| other.start_pos)
+                .filter(|pos| board_state.get_tile_at_pos(*pos).get_piece() == &Some(piece))
+                .collect();
+
+            if !others.is_empty() {
+                if others.iter().all(|pos| pos.get_file() != start.get_file()) {
+                    san.push_str(&start_name[..1]);
+                } else if others.iter().all(|pos| pos.get_rank() != start.get_rank()) {
+                    san.push_str(&start_name[1..]);
+                } else {
+                    san.push_str(&start_name);
+                }
+            }
+        }
+
+        if captures {
+            san.push('x');
+        }
+        san.push_str(&end.to_string());
+
+        if let Some(promotion) = self.promotion {
+            san.push('=');
+            san.push(promotion.symbol());
+        }
+
+        san
+    }
+
+    // the legal move for the player to move that the SAN describes
+    pub fn from_san(san: &str, board_state: &BoardState) -> Result<StoredMove, SanError> {
+        let parsed = ParsedSan::parse(san).ok_or_else(|| SanError::Malformed(String::from(san)))?;
+
+        let candidates: Vec<StoredMove> = MoveGeneration::gen_moves_for_player(board_state, *board_state.get_player_turn())
+            .into_iter()
+            .filter(|stored_move| parsed.matches(board_state, stored_move))
+            .collect();
+
+        match candidates.len() {
+            0 => Err(SanError::Illegal(String::from(san))),
+            1 => Ok(candidates[0]),
+            _ => Err(SanError::Ambiguous(String::from(san))),
+        }
+    }
+}
+
+fn is_castle(board_state: &BoardState, stored_move: &StoredMove) -> bool {
+    let piece = board_state.get_tile_at_pos(stored_move.start_pos).get_piece();
+    let files_moved = (stored_move.start_pos.get_file() as i32 - stored_move.end_pos.get_file() as i32).abs();
+
+    matches!(piece, Some(piece) if piece.get_piece_type() == &PieceType::King) && files_moved == 2
+}
+
+// the piece type for an upper case SAN letter, pawns are never written with a letter
+fn piece_type_from_letter(letter: char) -> Option<PieceType> {
+    if !letter.is_ascii_uppercase() {
+        return None;
+    }
+    Piece::from_fen_symbol(letter)
+        .map(|piece| *piece.get_piece_type())
+        .filter(|piece_type| piece_type != &PieceType::Pawn)
+}
+
+// what a SAN string says about a move, before it is matched against the legal moves
+enum ParsedSan {
+    // the file the king lands on, 6 for O-O and 2 for O-O-O
+    Castle(usize),
+    Move {
+        piece_type: PieceType,
+        end: Square,
+        // the start file and rank, when given to disambiguate
+        file: Option<usize>,
+        rank: Option<usize>,
+        promotion: Option<PieceType>,
+    },
+}
+
+impl ParsedSan {
+    fn parse(san: &str) -> Option<ParsedSan> {
+        let san = san.trim_end_matches(['+', '#', '!', '?']);
+
+        match san {
+            "O-O" | "0-0" => return Some(ParsedSan::Castle(6)),
+            "O-O-O" | "0-0-0" => return Some(ParsedSan::Castle(2)),
+            _ => (),
+        }
+
+        let mut chars: Vec<char> = san.chars().collect();
+
+        let mut promotion = None;
+        if chars.len() > 2 && chars[chars.len() - 1].is_ascii_uppercase() {
+            // a pawn can only become a queen, rook, bishop or knight
+            promotion = Some(piece_type_from_letter(chars.pop().unwrap()).filter(|piece_type| piece_type != &PieceType::King)?);
+            if chars.last() == Some(&'=') {
+                chars.pop();
+            }
+        }
+
+        let piece_type = match chars.first() {
+            Some(letter) if letter.is_ascii_uppercase() => {
+                let piece_type = piece_type_from_letter(*letter)?;
+                chars.remove(0);
+                piece_type
+            },
+            _ => PieceType::Pawn,
+        };
+
+        if chars.len() < 2 {
+            return None;
+        }
+        let end_name: String = chars.split_off(chars.len() - 2).into_iter().collect();
+        let end = Square::from_algebraic(&end_name)?;
+
+        if chars.last() == Some(&'x') {
+            chars.pop();
+        }
+
+        // whatever is left disambiguates the start, by file, rank or both
+        let mut file = None;
+        let mut rank = None;
+        for c in chars {
+            match c {
+                'a'..='h' if file.is_none() && rank.is_none() => file = Some(c as usize - 'a' as usize),
+                '1'..='8' if rank.is_none() => rank = Some(c as usize - '1' as usize),
+                _ => return None,
+            }
+        }
+
+        Some(ParsedSan::Move { piece_type, end, file, rank, promotion })
+    }
+
+    fn matches(&self, board_state: &BoardState, stored_move: &StoredMove) -> bool {
+        match self {
+            ParsedSan::Castle(end_file) => {
+                is_castle(board_state, stored_move) && stored_move.end_pos.get_file() == *end_file
+            },
+            ParsedSan::Move { piece_type, end, file, rank, promotion } => {
+                let start = stored_move.start_pos;
+                let moved_type = board_state.get_tile_at_pos(start).get_piece().map(|piece| *piece.get_piece_type());
+
+                moved_type == Some(*piece_type)
+                    && stored_move.end_pos == *end
+                    && !is_castle(board_state, stored_move)
+                    && file.is_none_or(|file| start.get_file() == file)
+                    && rank.is_none_or(|rank| start.get_rank() == rank)
+                    && stored_move.promotion.map(|piece| *piece.get_piece_type()) == *promotion
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board_state::square::sq;
+    use crate::board_state::Player;
+
+    fn mv(start: &str, end: &str) -> StoredMove {
+        StoredMove { start_pos: sq(start), end_pos: sq(end), promotion: None }
+    }
+
+    fn promote(start: &str, end: &str, piece_type: PieceType, player: Player) -> StoredMove {
+        StoredMove { start_pos: sq(start), end_pos: sq(end), promotion: Some(Piece::new(piece_type, player)) }
+    }
+
+    #[test]
+    fn to_san_pawn_and_piece_moves() {
+        let board_state = BoardState::new();
+
+        assert_eq!(mv("e2", "e4").to_san(&board_state), "e4");
+        assert_eq!(mv("g1", "f3").to_san(&board_state), "Nf3");
+    }
+
+    #[test]
+    fn to_san_captures() {
+        let board_state = BoardState::from_fen("4k3/8/8/3p4/4P3/8/8/3QK3 w - - 0 1").unwrap();
+
+        assert_eq!(mv("e4", "d5").to_san(&board_state), "exd5");
+        assert_eq!(mv("d1", "d5").to_san(&board_state), "Qxd5");
+    }
+
+    #[test]
+    fn to_san_en_passant() {
+        let board_state = BoardState::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1").unwrap();
+
+        assert_eq!(mv("e5", "d6").to_san(&board_state), "exd6");
+    }
+
+    #[test]
+    fn to_san_castles() {
+        let board_state = BoardState::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+
+        assert_eq!(mv("e1", "g1").to_san(&board_state), "O-O");
+        assert_eq!(mv("e1", "c1").to_san(&board_state), "O-O-O");
+    }
+
+    #[test]
+    fn to_san_promotions() {
+        let board_state = BoardState::from_fen("3r1k2/4P3/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+
+        assert_eq!(promote("e7", "d8", PieceType::Knight, Player::White).to_san(&board_state), "exd8=N");
+        assert_eq!(promote("e7", "e8", PieceType::Queen, Player::White).to_san(&board_state), "e8=Q");
+    }
+
+    #[test]
+    fn to_san_disambiguation() {
+        // knights on b1 and f1 share d2, rooks on a1 and a4 share a2, and queens on
+        // a1, a3 and c1 share b2, where neither the file nor the rank alone is enough
+        let by_file = BoardState::from_fen("4k3/8/8/8/8/8/8/1N2KN2 w - - 0 1").unwrap();
+        let by_rank = BoardState::from_fen("4k3/8/8/8/R7/8/8/R3K3 w - - 0 1").unwrap();
+        let by_both = BoardState::from_fen("4k3/8/8/8/8/Q7/8/Q1Q1K3 w - - 0 1").unwrap();
+
+        assert_eq!(mv("b1", "d2").to_san(&by_file), "Nbd2");
+        assert_eq!(mv("a1", "a2").to_san(&by_rank), "R1a2");
+        assert_eq!(mv("a1", "b2").to_san(&by_both), "Qa1b2");
+        assert_eq!(mv("c1", "b2").to_san(&by_both), "Qcb2");
+    }
+
+    #[test]
+    fn to_san_never_marks_check() {
+        // the queen attacks the king after this move, which would be "Qe7+" in ordinary chess
+        let board_state = BoardState::from_fen("4k3/8/8/8/8/8/8/4QK2 w - - 0 1").unwrap();
+
+        assert_eq!(mv("e1", "e7").to_san(&board_state), "Qe7");
+    }
+
+    #[test]
+    fn from_san_ignores_check_markers_and_annotations() {
+        let board_state = BoardState::from_fen("4k3/8/8/8/8/8/8/4QK2 w - - 0 1").unwrap();
+
+        for san in ["Qe7", "Qe7+", "Qe7#", "Qe7!?", "Qe7+!"] {
+            assert_eq!(StoredMove::from_san(san, &board_state), Ok(mv("e1", "e7")), "{}", san);
+        }
+    }
+
+    #[test]
+    fn from_san_round_trips_every_legal_move() {
+        let positions = [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "4k3/8/8/8/8/Q7/8/Q1Q1K3 w - - 0 1",
+            "3r1k2/4P3/8/8/8/8/8/4K3 w - - 0 1",
+            "4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1",
+        ];
+
+        for fen in positions {
+            let board_state = BoardState::from_fen(fen).unwrap();
+            for stored_move in MoveGeneration::gen_moves_for_player(&board_state, Player::White) {
+                let san = stored_move.to_san(&board_state);
+
+                assert_eq!(StoredMove::from_san(&san, &board_state), Ok(stored_move), "{} in {}", san, fen);
+            }
+        }
+    }
+
+    #[test]
+    fn from_san_castles_with_letters_or_zeros() {
+        let board_state = BoardState::from_fen("r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1").unwrap();
+
+        assert_eq!(StoredMove::from_san("O-O", &board_state), Ok(mv("e8", "g8")));
+        assert_eq!(StoredMove::from_san("0-0-0", &board_state), Ok(mv("e8", "c8")));
+        // the castle has to be written as a castle, not as a two file king move
+        assert_eq!(StoredMove::from_san("Kg8", &board_state), Err(SanError::Illegal(String::from("Kg8"))));
+    }
+
+    #[test]
+    fn from_san_errors() {
+        let board_state = BoardState::from_fen("4k3/8/8/8/8/8/8/1N2KN2 w - - 0 1").unwrap();
+
+        assert_eq!(StoredMove::from_san("Nd2", &board_state), Err(SanError::Ambiguous(String::from("Nd2"))));
+        assert_eq!(StoredMove::from_san("Nd3", &board_state), Err(SanError::Illegal(String::from("Nd3"))));
+        for malformed in ["", "Zd2", "e2-e4", "Nd9", "N", "e8=K2"] {
+            assert_eq!(
+                StoredMove::from_san(malformed, &board_state),
+                Err(SanError::Malformed(String::from(malformed)))
+            );
+        }
+    }
+
+    #[test]
+    fn from_san_promotion_must_match() {
+        let board_state = BoardState::from_fen("4k3/P7/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+
+        assert_eq!(
+            StoredMove::from_san("a8=R", &board_state),
+            Ok(promote("a7", "a8", PieceType::Rook, Player::White))
+        );
+        assert_eq!(StoredMove::from_san("a8", &board_state), Err(SanError::Illegal(String::from("a8"))));
+        for malformed in ["a8=K", "a8=P", "a8K"] {
+            assert_eq!(
+                StoredMove::from_san(malformed, &board_state),
+                Err(SanError::Malformed(String::from(malformed)))
+            );
+        }
+    }
+}
@@ -0,0 +1,34 @@
+use std::fmt;
+
+// The reasons StoredMove::from_san can refuse a move, each holding the SAN it was given
+#[derive(Debug, Clone, PartialEq)]
+pub enum SanError {
+    // not written as SAN at all, such as "Zz9" or "e2-e4"
+    Malformed(String),
+    // well formed, but no legal move in the position matches it
+    Illegal(String),
+    // more than one legal move matches it, such as "Nd2" with knights on b1 and f1
+    Ambiguous(String),
+}
+
+impl fmt::Display for SanError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SanError::Malformed(san) => write!(f, "'{}' is not standard algebraic notation", san),
+            SanError::Illegal(san) => write!(f, "'{}' is not a legal move in this position", san),
+            SanError::Ambiguous(san) => write!(f, "'{}' matches more than one legal move", san),
+        }
+    }
+}
+
+impl std::error::Error for SanError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_ambiguous() {
+        assert_eq!(SanError::Ambiguous(String::from("Nd2")).to_string(), "'Nd2' matches more than one legal move");
+    }
+}
@@ -0,0 +1,173 @@
+use std::fmt;
+
+// A tile on the board, as a file (column a to h) and a rank (row 1 to 8)
+// both are stored from 0, so a1 is file 0, rank 0 and h8 is file 7, rank 7
+// a Square can only be made through the checked constructors, so it is always on the board
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Square {
+    file: u8,
+    rank: u8,
+}
+
+impl Square {
+    pub fn new(file: usize, rank: usize) -> Option<Square> {
+        if file < 8 && rank < 8 {
+            Some(Square { file: file as u8, rank: rank as u8 })
+        } else {
+            None
+        }
+    }
+
+    // parses names such as "e4", with the file as a lowercase letter
+    pub fn from_algebraic(name: &str) -> Option<Square> {
+        let mut chars = name.chars();
+        let (file, rank) = match (chars.next(), chars.next(), chars.next()) {
+            (Some(file), Some(rank), None) => (file, rank),
+            _ => return None,
+        };
+
+        if !('a'..='h').contains(&file) || !('1'..='8').contains(&rank) {
+            return None;
+        }
+
+        Square::new(file as usize - 'a' as usize, rank as usize - '1' as usize)
+    }
+
+    pub fn get_file(&self) -> usize {
+        self.file as usize
+    }
+
+    pub fn get_rank(&self) -> usize {
+        self.rank as usize
+    }
+
+    // the square's bit in a bitboard, counting a1 as 0, h1 as 7 and h8 as 63
+    pub fn index(&self) -> usize {
+        self.rank as usize * 8 + self.file as usize
+    }
+
+    pub fn from_index(index: usize) -> Option<Square> {
+        if index < 64 {
+            Square::new(index % 8, index / 8)
+        } else {
+            None
+        }
+    }
+
+    // the square moved by the given number of files and ranks, if it is still on the board
+    pub fn offset(&self, files: i32, ranks: i32) -> Option<Square> {
+        let file = self.file as i32 + files;
+        let rank = self.rank as i32 + ranks;
+
+        if file < 0 || rank < 0 {
+            return None;
+        }
+        Square::new(file as usize, rank as usize)
+    }
+
+    // every square, from a1 to h1, then a2 to h2 and so on
+    pub fn all() -> impl Iterator<Item = Square> {
+        (0..8).flat_map(|rank| (0..8).map(move |file| Square { file, rank }))
+    }
+}
+
+// shorthand for naming squares in tests, panics if name is not a square
+#[cfg(test)]
+pub(crate) fn sq(name: &str) -> Square {
+    Square::from_algebraic(name).unwrap()
+}
+
+impl fmt::Display for Square {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", (b'a' + self.file) as char, (b'1' + self.rank) as char)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_in_bounds() {
+        let square = Square::new(4, 3).unwrap();
+
+        assert_eq!(square.get_file(), 4);
+        assert_eq!(square.get_rank(), 3);
+    }
+
+    #[test]
+    fn new_out_of_bounds() {
+        assert_eq!(Square::new(8, 0), None);
+        assert_eq!(Square::new(0, 8), None);
+        assert_eq!(Square::new(255, 255), None);
+    }
+
+    #[test]
+    fn from_algebraic_corners() {
+        assert_eq!(Square::from_algebraic("a1"), Square::new(0, 0));
+        assert_eq!(Square::from_algebraic("h1"), Square::new(7, 0));
+        assert_eq!(Square::from_algebraic("a8"), Square::new(0, 7));
+        assert_eq!(Square::from_algebraic("h8"), Square::new(7, 7));
+    }
+
+    #[test]
+    fn from_algebraic_e4() {
+        assert_eq!(Square::from_algebraic("e4"), Square::new(4, 3));
+    }
+
+    #[test]
+    fn from_algebraic_invalid() {
+        for name in ["", "e", "e9", "i4", "e0", "E4", "e44", "4e"] {
+            assert_eq!(Square::from_algebraic(name), None, "{}", name);
+        }
+    }
+
+    #[test]
+    fn display_round_trip() {
+        for square in Square::all() {
+            assert_eq!(Square::from_algebraic(&square.to_string()), Some(square));
+        }
+    }
+
+    #[test]
+    fn display_e4() {
+        assert_eq!(Square::new(4, 3).unwrap().to_string(), "e4");
+    }
+
+    #[test]
+    fn offset_on_board() {
+        let square = Square::from_algebraic("e4").unwrap();
+
+        assert_eq!(square.offset(1, 2), Square::from_algebraic("f6"));
+        assert_eq!(square.offset(-4, -3), Square::from_algebraic("a1"));
+    }
+
+    #[test]
+    fn offset_off_board() {
+        let square = Square::from_algebraic("a1").unwrap();
+
+        assert_eq!(square.offset(-1, -1), None);
+        assert_eq!(square.offset(0, 8), None);
+        assert_eq!(square.offset(8, 0), None);
+    }
+
+    #[test]
+    fn index_round_trip() {
+        for square in Square::all() {
+            assert_eq!(Square::from_index(square.index()), Some(square));
+        }
+        assert_eq!(Square::from_algebraic("h1").unwrap().index(), 7);
+        assert_eq!(Square::from_algebraic("a2").unwrap().index(), 8);
+        assert_eq!(Square::from_index(64), None);
+    }
+
+    #[test]
+    fn all_has_every_square_once() {
+        let squares: Vec<Square> = Square::all().collect();
+
+        assert_eq!(squares.len(), 64);
+        assert_eq!(squares[0], Square::new(0, 0).unwrap());
+        assert_eq!(squares[63], Square::new(7, 7).unwrap());
+        assert!(squares.iter().enumerate().all(|(index, square)| !squares[index + 1..].contains(square)));
+    }
+}
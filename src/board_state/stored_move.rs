@@ -1,9 +1,19 @@
-use crate::board_state::Piece;
+use std::fmt;
+
+use crate::board_state::{ Piece, Square };
 
 // Since there is no mutability here, it is fine to have public data
 // We only make new Stored moves and get the data from them
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub struct StoredMove {
-    pub start_pos: (usize, usize),
-    pub end_pos: (usize, usize),
+    pub start_pos: Square,
+    pub end_pos: Square,
     pub promotion: Option<Piece>,
-}
\ No newline at end of file
+}
+
+// written as UCI long algebraic notation, such as "e2e4", see StoredMove::to_uci
+impl fmt::Display for StoredMove {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_uci())
+    }
+}
@@ -1,10 +1,11 @@
 
 pub use crate::board_state::piece::Piece;
+use crate::board_state::Square;
 
 #[derive(Debug, Copy, Clone)]
 pub struct Tile {
     piece: Option<Piece>,
-    pos: (usize, usize),
+    pos: Square,
 }
 
 impl PartialEq for Tile {
@@ -13,15 +14,15 @@ impl PartialEq for Tile {
             return false
         }
         match (&self.piece, &other.piece) {
-            (&Some(ref lhs), &Some(ref rhs)) if lhs == rhs => true,
-            (&None, &None) => true,
+            (Some(lhs), Some(rhs)) if lhs == rhs => true,
+            (None, None) => true,
             _ => false
         }
     }
 }
 
 impl Tile {
-    pub fn new(piece: Option<Piece>, pos: (usize, usize)) -> Tile {
+    pub fn new(piece: Option<Piece>, pos: Square) -> Tile {
         Tile { piece, pos }
     }
 
@@ -29,7 +30,7 @@ impl Tile {
         &self.piece
     }
 
-    pub fn get_pos(&self) -> &(usize, usize) {
+    pub fn get_pos(&self) -> &Square {
         &self.pos
     }
 
@@ -51,93 +52,94 @@ impl Tile {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::board_state::square::sq;
     use crate::board_state::{PieceType, Player};
 
     #[test]
     fn equal_none() {
-        let tile1 = Tile::new(None, (0, 0));
-        let tile2 = Tile::new(None, (0, 0));
+        let tile1 = Tile::new(None, sq("a1"));
+        let tile2 = Tile::new(None, sq("a1"));
 
         assert_eq!(tile1, tile2);
     }
 
     #[test]
     fn equal_w_king() {
-        let tile1 = Tile::new(Some(Piece::new(PieceType::King, Player::White)), (0, 0));
-        let tile2 = Tile::new(Some(Piece::new(PieceType::King, Player::White)), (0, 0));
+        let tile1 = Tile::new(Some(Piece::new(PieceType::King, Player::White)), sq("a1"));
+        let tile2 = Tile::new(Some(Piece::new(PieceType::King, Player::White)), sq("a1"));
 
         assert_eq!(tile1, tile2);
     }
 
     #[test]
     fn not_equal_diff_player() {
-        let tile1 = Tile::new(Some(Piece::new(PieceType::King, Player::Black)), (0, 0));
-        let tile2 = Tile::new(Some(Piece::new(PieceType::King, Player::White)), (0, 0));
+        let tile1 = Tile::new(Some(Piece::new(PieceType::King, Player::Black)), sq("a1"));
+        let tile2 = Tile::new(Some(Piece::new(PieceType::King, Player::White)), sq("a1"));
 
         assert_ne!(tile1, tile2);
     }
 
     #[test]
     fn not_equal_diff_piece() {
-        let tile1 = Tile::new(None, (0, 0));
-        let tile2 = Tile::new(Some(Piece::new(PieceType::King, Player::White)), (0, 0));
+        let tile1 = Tile::new(None, sq("a1"));
+        let tile2 = Tile::new(Some(Piece::new(PieceType::King, Player::White)), sq("a1"));
 
         assert_ne!(tile1, tile2);
     }
 
     #[test]
     fn not_equal_diff_pos() {
-        let tile1 = Tile::new(None, (0, 0));
-        let tile2 = Tile::new(None, (0, 1));
+        let tile1 = Tile::new(None, sq("a1"));
+        let tile2 = Tile::new(None, sq("a2"));
 
         assert_ne!(tile1, tile2);
     }
 
     #[test]
     fn get_piece_none() {
-        let tile = Tile::new(None, (0, 0));
+        let tile = Tile::new(None, sq("a1"));
 
         assert_eq!(tile.get_piece(), &None);
     }
 
     #[test]
     fn get_piece_w_king() {
-        let tile = Tile::new(Some(Piece::new(PieceType::King, Player::White)), (0, 0));
+        let tile = Tile::new(Some(Piece::new(PieceType::King, Player::White)), sq("a1"));
 
         assert_eq!(tile.get_piece(), &Some(Piece::new(PieceType::King, Player::White)));
     }
 
     #[test]
     fn get_pos() {
-        let tile = Tile::new(None, (0, 0));
+        let tile = Tile::new(None, sq("a1"));
 
-        assert_eq!(tile.get_pos(), &(0, 0));
+        assert_eq!(tile.get_pos(), &sq("a1"));
     }
 
     #[test]
     fn symbol_w_king() {
-        let tile = Tile::new(Some(Piece::new(PieceType::King, Player::White)), (0, 0));
+        let tile = Tile::new(Some(Piece::new(PieceType::King, Player::White)), sq("a1"));
 
         assert_eq!(tile.symbol(), 'K');
     }
 
     #[test]
     fn symbol_none() {
-        let tile = Tile::new(None, (0,0));
+        let tile = Tile::new(None, sq("a1"));
 
         assert_eq!(tile.symbol(), '.');
     }
 
     #[test]
     fn symbol_utf_w_king() {
-        let tile = Tile::new(Some(Piece::new(PieceType::King, Player::White)), (0,0));
+        let tile = Tile::new(Some(Piece::new(PieceType::King, Player::White)), sq("a1"));
 
         assert_eq!(tile.symbol_utf(), '\u{2654}');
     }
 
     #[test]
     fn symbol_utf_none() {
-        let tile = Tile::new(None, (0,0));
+        let tile = Tile::new(None, sq("a1"));
 
         assert_eq!(tile.symbol_utf(), '.');
     }
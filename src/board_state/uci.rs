@@ -0,0 +1,128 @@
+use crate::board_state::{ Piece, PieceType, Player, Square, StoredMove, UciError };
+
+// UCI style long algebraic notation, the start and end squares followed by a lower case
+// promotion letter if there is one, such as "e2e4" or "e7e8q"
+// unlike SAN this needs no board to read or write, so it is what bots and the network use
+impl StoredMove {
+    pub fn to_uci(&self) -> String {
+        let mut uci = format!("{}{}", self.start_pos, self.end_pos);
+        if let Some(promotion) = self.promotion {
+            uci.push(promotion.fen_symbol().to_ascii_lowercase());
+        }
+
+        uci
+    }
+
+    // the player a promotion belongs to comes from the last rank it lands on, 8 for white and 1 for black
+    // whether the move is legal is not checked here, see BoardState::try_move for that
+    pub fn from_uci(uci: &str) -> Result<StoredMove, UciError> {
+        let chars: Vec<char> = uci.chars().collect();
+        if chars.len() != 4 && chars.len() != 5 {
+            return Err(UciError::WrongLength(String::from(uci)));
+        }
+
+        let square = |index: usize| {
+            let name: String = chars[index..index + 2].iter().collect();
+            Square::from_algebraic(&name).ok_or(UciError::InvalidSquare(name))
+        };
+        let start_pos = square(0)?;
+        let end_pos = square(2)?;
+
+        let promotion = match chars.get(4) {
+            Some(letter) => {
+                let player = match end_pos.get_rank() {
+                    7 => Player::White,
+                    0 => Player::Black,
+                    _ => return Err(UciError::InvalidPromotion(String::from(uci))),
+                };
+                let piece_type = match letter {
+                    'q' => PieceType::Queen,
+                    'r' => PieceType::Rook,
+                    'b' => PieceType::Bishop,
+                    'n' => PieceType::Knight,
+                    _ => return Err(UciError::InvalidPromotion(String::from(uci))),
+                };
+                Some(Piece::new(piece_type, player))
+            },
+            None => None,
+        };
+
+        Ok(StoredMove { start_pos, end_pos, promotion })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board_state::square::sq;
+    use crate::board_state::BoardState;
+    use crate::move_generation::MoveGeneration;
+
+    #[test]
+    fn to_uci_plain_move() {
+        let stored_move = StoredMove { start_pos: sq("e2"), end_pos: sq("e4"), promotion: None };
+
+        assert_eq!(stored_move.to_uci(), "e2e4");
+        assert_eq!(stored_move.to_string(), "e2e4");
+    }
+
+    #[test]
+    fn to_uci_promotion() {
+        let stored_move = StoredMove {
+            start_pos: sq("e7"),
+            end_pos: sq("e8"),
+            promotion: Some(Piece::new(PieceType::Queen, Player::White)),
+        };
+
+        assert_eq!(stored_move.to_uci(), "e7e8q");
+    }
+
+    #[test]
+    fn from_uci_promotions_by_player() {
+        let white = StoredMove::from_uci("b7a8n").unwrap();
+        let black = StoredMove::from_uci("h2h1r").unwrap();
+
+        assert_eq!(white.promotion, Some(Piece::new(PieceType::Knight, Player::White)));
+        assert_eq!(black.promotion, Some(Piece::new(PieceType::Rook, Player::Black)));
+        assert_eq!(black.start_pos, sq("h2"));
+        assert_eq!(black.end_pos, sq("h1"));
+    }
+
+    #[test]
+    fn round_trips_every_legal_move() {
+        let fens = [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "r3k2r/1P6/8/8/8/8/6p1/R3K2R w KQkq - 0 1",
+            "r3k2r/1P6/8/8/8/8/6p1/R3K2R b KQkq - 0 1",
+        ];
+
+        for fen in fens {
+            let board_state = BoardState::from_fen(fen).unwrap();
+            for stored_move in MoveGeneration::gen_moves_for_player(&board_state, *board_state.get_player_turn()) {
+                assert_eq!(StoredMove::from_uci(&stored_move.to_uci()), Ok(stored_move));
+            }
+        }
+    }
+
+    #[test]
+    fn from_uci_wrong_length() {
+        for uci in ["", "e2e", "e2e4qq", "e2e4e5"] {
+            assert_eq!(StoredMove::from_uci(uci), Err(UciError::WrongLength(String::from(uci))));
+        }
+    }
+
+    #[test]
+    fn from_uci_off_board_squares() {
+        assert_eq!(StoredMove::from_uci("i2i4"), Err(UciError::InvalidSquare(String::from("i2"))));
+        assert_eq!(StoredMove::from_uci("a8a9"), Err(UciError::InvalidSquare(String::from("a9"))));
+        assert_eq!(StoredMove::from_uci("a0a1"), Err(UciError::InvalidSquare(String::from("a0"))));
+        assert_eq!(StoredMove::from_uci("E2E4"), Err(UciError::InvalidSquare(String::from("E2"))));
+    }
+
+    #[test]
+    fn from_uci_invalid_promotion() {
+        assert_eq!(StoredMove::from_uci("e7e8k"), Err(UciError::InvalidPromotion(String::from("e7e8k"))));
+        assert_eq!(StoredMove::from_uci("e7e8Q"), Err(UciError::InvalidPromotion(String::from("e7e8Q"))));
+        assert_eq!(StoredMove::from_uci("e2e4q"), Err(UciError::InvalidPromotion(String::from("e2e4q"))));
+    }
+}
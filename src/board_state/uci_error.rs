@@ -0,0 +1,34 @@
+use std::fmt;
+
+// The reasons StoredMove::from_uci can refuse a move string
+#[derive(Debug, Clone, PartialEq)]
+pub enum UciError {
+    // a move is four characters, or five with a promotion, such as "e2e4" or "e7e8q"
+    WrongLength(String),
+    // the text of a square that is not on the board, such as "i9"
+    InvalidSquare(String),
+    // the promotion letter is not one of q, r, b or n, or the pawn is not reaching the last rank
+    InvalidPromotion(String),
+}
+
+impl fmt::Display for UciError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            UciError::WrongLength(text) => write!(f, "'{}' is not 4 or 5 characters long", text),
+            UciError::InvalidSquare(square) => write!(f, "'{}' is not a square on the board", square),
+            UciError::InvalidPromotion(text) => write!(f, "'{}' does not have a valid promotion", text),
+        }
+    }
+}
+
+impl std::error::Error for UciError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_invalid_square() {
+        assert_eq!(UciError::InvalidSquare(String::from("i9")).to_string(), "'i9' is not a square on the board");
+    }
+}
@@ -0,0 +1,49 @@
+use crate::board_state::{ CastlingRights, GameStatus, Piece, Square, StoredMove };
+
+// Everything BoardState::make_move overwrites, so BoardState::unmake_move can put it back
+// only BoardState builds these, since unmaking a made up record would corrupt the board
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct UndoInfo {
+    pub(crate) stored_move: StoredMove,
+    // the piece as it was on the starting tile, before any promotion
+    pub(crate) moved_piece: Piece,
+    // the captured piece and the tile it was taken from, like MoveRecord
+    pub(crate) captured: Option<(Piece, Square)>,
+    pub(crate) castling_rights: CastlingRights,
+    pub(crate) en_passant: Option<Square>,
+    pub(crate) fifty_move_rule_count: usize,
+    pub(crate) fullmove_number: usize,
+    pub(crate) game_status: GameStatus,
+}
+
+impl UndoInfo {
+    pub fn get_stored_move(&self) -> &StoredMove {
+        &self.stored_move
+    }
+
+    pub fn get_moved_piece(&self) -> &Piece {
+        &self.moved_piece
+    }
+
+    pub fn captured_piece(&self) -> Option<Piece> {
+        self.captured.map(|(piece, _)| piece)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::board_state::{ BoardState, Piece, PieceType, Player, StoredMove };
+    use crate::board_state::square::sq;
+
+    #[test]
+    fn records_move_and_capture() {
+        let mut board_state = BoardState::from_fen("4k3/8/8/3p4/4P3/8/8/4K3 w - - 0 1").unwrap();
+        let capture = StoredMove { start_pos: sq("e4"), end_pos: sq("d5"), promotion: None };
+
+        let undo_info = board_state.make_move(capture);
+
+        assert_eq!(undo_info.get_stored_move(), &capture);
+        assert_eq!(undo_info.get_moved_piece(), &Piece::new(PieceType::Pawn, Player::White));
+        assert_eq!(undo_info.captured_piece(), Some(Piece::new(PieceType::Pawn, Player::Black)));
+    }
+}
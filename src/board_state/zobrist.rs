@@ -0,0 +1,197 @@
+use crate::board_state::{ Bitboards, BoardState, CastlingRights, Piece, Player, Square };
+
+// Zobrist hashing gives every (piece, square) pair, the side to move, each castling right and each
+// en passant file a fixed random key. a position's hash key is the xor of the keys that apply to it,
+// so a move only has to xor out what it removes and xor in what it adds
+
+// indexed [player][piece type][square], using the same order as Bitboards
+const PIECE_KEYS: [[[u64; 64]; 6]; 2] = piece_keys();
+const BLACK_TO_MOVE_KEY: u64 = splitmix64(1 << 20);
+// white kingside, white queenside, black kingside, black queenside
+const CASTLING_KEYS: [u64; 4] = [
+    splitmix64((1 << 20) + 1),
+    splitmix64((1 << 20) + 2),
+    splitmix64((1 << 20) + 3),
+    splitmix64((1 << 20) + 4),
+];
+const EN_PASSANT_FILE_KEYS: [u64; 8] = en_passant_file_keys();
+
+// a fixed mix of the counter, so the keys are the same on every run and every machine
+const fn splitmix64(counter: u64) -> u64 {
+    let mut z = counter.wrapping_add(1).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+const fn piece_keys() -> [[[u64; 64]; 6]; 2] {
+    let mut keys = [[[0; 64]; 6]; 2];
+
+    let mut counter = 0;
+    while counter < 2 * 6 * 64 {
+        keys[counter / (6 * 64)][counter / 64 % 6][counter % 64] = splitmix64(counter as u64);
+        counter += 1;
+    }
+    keys
+}
+
+const fn en_passant_file_keys() -> [u64; 8] {
+    let mut keys = [0; 8];
+
+    let mut file = 0;
+    while file < 8 {
+        keys[file] = splitmix64((1 << 20) + 5 + file as u64);
+        file += 1;
+    }
+    keys
+}
+
+impl BoardState {
+    // the Zobrist key of the position, kept up to date as moves are made and unmade
+    // two positions with the same pieces, side to move, castling rights and en passant file share a key
+    pub fn hash_key(&self) -> u64 {
+        self.hash_key
+    }
+
+    // the key worked out from nothing, which the incremental key should always equal
+    pub(crate) fn compute_hash_key(&self) -> u64 {
+        Square::all().fold(self.state_hash_key(), |key, pos| {
+            key ^ BoardState::piece_hash_key(*self.get_tile_at_pos(pos).get_piece(), pos)
+        })
+    }
+
+    pub(crate) fn piece_hash_key(piece: Option<Piece>, pos: Square) -> u64 {
+        match piece {
+            Some(piece) => {
+                let player = Bitboards::player_index(*piece.get_player());
+                PIECE_KEYS[player][Bitboards::piece_type_index(*piece.get_piece_type())][pos.index()]
+            },
+            None => 0,
+        }
+    }
+
+    // the part of the key that is not piece placement. move_piece and unmake_move xor this out
+    // before changing the side to move, castling rights or en passant tile, and back in after
+    pub(crate) fn state_hash_key(&self) -> u64 {
+        let mut key = BoardState::castling_hash_key(&self.castling_rights);
+
+        if self.player_turn == Player::Black {
+            key ^= BLACK_TO_MOVE_KEY;
+        }
+        if let Some(pos) = self.en_passant {
+            key ^= EN_PASSANT_FILE_KEYS[pos.get_file()];
+        }
+        key
+    }
+
+    fn castling_hash_key(castling_rights: &CastlingRights) -> u64 {
+        let rights = [
+            castling_rights.can_castle_kingside(Player::White),
+            castling_rights.can_castle_queenside(Player::White),
+            castling_rights.can_castle_kingside(Player::Black),
+            castling_rights.can_castle_queenside(Player::Black),
+        ];
+
+        rights.iter().zip(CASTLING_KEYS.iter())
+            .filter(|(allowed, _)| **allowed)
+            .fold(0, |key, (_, castling_key)| key ^ castling_key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board_state::square::sq;
+    use crate::board_state::StoredMove;
+    use crate::move_generation::MoveGeneration;
+    use crate::rng::Rng;
+
+    fn mv(start: &str, end: &str) -> StoredMove {
+        StoredMove { start_pos: sq(start), end_pos: sq(end), promotion: None }
+    }
+
+    #[test]
+    fn keys_are_distinct() {
+        let mut keys: Vec<u64> = PIECE_KEYS.iter().flatten().flatten().copied().collect();
+        keys.push(BLACK_TO_MOVE_KEY);
+        keys.extend_from_slice(&CASTLING_KEYS);
+        keys.extend_from_slice(&EN_PASSANT_FILE_KEYS);
+
+        keys.sort_unstable();
+        keys.dedup();
+        assert_eq!(keys.len(), 2 * 6 * 64 + 1 + 4 + 8);
+    }
+
+    #[test]
+    fn new_board_key_matches_recompute() {
+        let board_state = BoardState::new();
+
+        assert_eq!(board_state.hash_key(), board_state.compute_hash_key());
+        assert_ne!(board_state.hash_key(), 0);
+    }
+
+    #[test]
+    fn from_fen_key_matches_recompute() {
+        let board_state = BoardState::from_fen("r3k2r/8/8/3pP3/8/8/8/R3K2R w Kq d6 0 1").unwrap();
+
+        assert_eq!(board_state.hash_key(), board_state.compute_hash_key());
+    }
+
+    #[test]
+    fn transpositions_share_a_key() {
+        let mut first = BoardState::new();
+        let mut second = BoardState::new();
+
+        for stored_move in [mv("g1", "f3"), mv("g8", "f6"), mv("b1", "c3")] {
+            first.move_piece(stored_move);
+        }
+        for stored_move in [mv("b1", "c3"), mv("g8", "f6"), mv("g1", "f3")] {
+            second.move_piece(stored_move);
+        }
+
+        assert_eq!(first.hash_key(), second.hash_key());
+    }
+
+    #[test]
+    fn side_to_move_castling_and_en_passant_change_the_key() {
+        let with_black = BoardState::from_fen("4k3/8/8/8/8/8/8/4K2R b - - 0 1").unwrap();
+        let with_white = BoardState::from_fen("4k3/8/8/8/8/8/8/4K2R w - - 0 1").unwrap();
+        let with_castling = BoardState::from_fen("4k3/8/8/8/8/8/8/4K2R w K - 0 1").unwrap();
+        let with_en_passant = BoardState::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1").unwrap();
+        let without_en_passant = BoardState::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - - 0 1").unwrap();
+
+        assert_ne!(with_black.hash_key(), with_white.hash_key());
+        assert_ne!(with_white.hash_key(), with_castling.hash_key());
+        assert_ne!(with_en_passant.hash_key(), without_en_passant.hash_key());
+    }
+
+    #[test]
+    fn incremental_key_matches_recompute_in_random_games() {
+        // seeded, so the random games are the same on every run
+        let mut rng = Rng::new(7);
+
+        for _ in 0..50 {
+            let mut board_state = BoardState::new();
+            let mut undo_infos = Vec::new();
+            let mut keys = Vec::new();
+
+            for _ in 0..200 {
+                let moves = MoveGeneration::gen_moves_for_player(&board_state, *board_state.get_player_turn());
+                if moves.is_empty() {
+                    break;
+                }
+                let stored_move = *rng.choose(&moves).unwrap();
+
+                keys.push(board_state.hash_key());
+                undo_infos.push(board_state.make_move(stored_move));
+                assert_eq!(board_state.hash_key(), board_state.compute_hash_key(), "after {}", stored_move);
+            }
+
+            // unmaking goes back through the same keys
+            while let Some(undo_info) = undo_infos.pop() {
+                board_state.unmake_move(undo_info);
+                assert_eq!(Some(board_state.hash_key()), keys.pop());
+            }
+        }
+    }
+}
@@ -0,0 +1,308 @@
+pub mod played_move;
+
+pub use crate::game::played_move::PlayedMove;
+
+use std::time::SystemTime;
+
+use crate::board_state::{ BoardState, FenError, GameRules, GameStatus, MoveError, Player, StoredMove };
+use crate::pgn::{ Pgn, PgnError };
+
+// A whole game - where it started, every move played since, and the current position
+// moves should go through the game rather than its BoardState, so the history always matches the board
+#[derive(Debug, Clone, PartialEq)]
+pub struct Game {
+    start_position: BoardState,
+    board_state: BoardState,
+    moves: Vec<PlayedMove>,
+    // name and value pairs such as ("White", "alice"), written as PGN tags
+    metadata: Vec<(String, String)>,
+}
+
+impl Game {
+    pub fn new() -> Game {
+        Game::from_board_state(BoardState::new())
+    }
+
+    pub fn with_rules(rules: GameRules) -> Game {
+        Game::from_board_state(BoardState::with_rules(rules))
+    }
+
+    // a game starting from a set up position, see BoardState::from_fen
+    pub fn from_fen(fen: &str) -> Result<Game, FenError> {
+        Ok(Game::from_board_state(BoardState::from_fen(fen)?))
+    }
+
+    fn from_board_state(board_state: BoardState) -> Game {
+        Game {
+            start_position: board_state.clone(),
+            board_state,
+            moves: Vec::new(),
+            metadata: Vec::new(),
+        }
+    }
+
+    // plays the move through BoardState::try_move and adds it to the history
+    pub fn try_move(&mut self, player: Player, planned_move: StoredMove) -> Result<&PlayedMove, MoveError> {
+        let record = self.board_state.try_move(player, planned_move)?;
+
+        self.moves.push(PlayedMove { record, timestamp: SystemTime::now() });
+        Ok(self.moves.last().unwrap())
+    }
+
+    pub fn resign(&mut self, player: Player) {
+        self.board_state.resign(player);
+    }
+
+    pub fn claim_fifty_move_draw(&mut self) -> bool {
+        self.board_state.claim_fifty_move_draw()
+    }
+
+    pub fn get_board_state(&self) -> &BoardState {
+        &self.board_state
+    }
+
+    pub fn get_start_position(&self) -> &BoardState {
+        &self.start_position
+    }
+
+    pub fn get_moves(&self) -> &[PlayedMove] {
+        &self.moves
+    }
+
+    pub fn get_result(&self) -> &GameStatus {
+        self.board_state.get_game_status()
+    }
+
+    // the number of moves played by both players
+    pub fn ply_count(&self) -> usize {
+        self.moves.len()
+    }
+
+    // the position after the first ply moves, so ply 0 is the start position and
+    // ply_count() is the current one, with any resignation or claimed draw. None past the end of the game
+    pub fn position_at_ply(&self, ply: usize) -> Option<BoardState> {
+        if ply > self.moves.len() {
+            return None;
+        }
+        if ply == self.moves.len() {
+            return Some(self.board_state.clone());
+        }
+
+        let mut board_state = self.start_position.clone();
+        for played_move in self.moves[..ply].iter() {
+            board_state.move_piece(*played_move.get_stored_move());
+        }
+
+        Some(board_state)
+    }
+
+    pub fn get_metadata(&self, name: &str) -> Option<&str> {
+        self.metadata.iter()
+            .find(|(metadata_name, _)| metadata_name == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    // replaces the value if the name is already there, otherwise adds it at the end
+    pub fn set_metadata(&mut self, name: &str, value: &str) {
+        match self.metadata.iter_mut().find(|(metadata_name, _)| metadata_name == name) {
+            Some(entry) => entry.1 = String::from(value),
+            None => self.metadata.push((String::from(name), String::from(value))),
+        }
+    }
+
+    // the game as PGN, with the metadata as tags and the result as it stands
+    // the moves are checked again by Pgn under the game's own rules
+    pub fn to_pgn(&self, visibility_comments: bool) -> Result<String, PgnError> {
+        let moves: Vec<StoredMove> = self.moves.iter().map(|played_move| *played_move.get_stored_move()).collect();
+        let rules = *self.start_position.get_rules();
+
+        // the usual starting position is left out of the tags
+        let start_fen = self.start_position.to_fen();
+        let fen = Some(start_fen.as_str()).filter(|fen| *fen != BoardState::new().to_fen());
+        let mut pgn = Pgn::with_rules(fen, &moves, rules)?;
+
+        for (name, value) in self.metadata.iter() {
+            pgn.set_tag(name, value);
+        }
+        pgn.set_result(self.get_result());
+
+        pgn.write(visibility_comments)
+    }
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board_state::square::sq;
+    use crate::board_state::{ Piece, PieceType, RepetitionMode };
+
+    fn mv(start: &str, end: &str) -> StoredMove {
+        StoredMove { start_pos: sq(start), end_pos: sq(end), promotion: None }
+    }
+
+    fn play(game: &mut Game, moves: &[StoredMove]) {
+        for stored_move in moves {
+            let player = *game.get_board_state().get_player_turn();
+            game.try_move(player, *stored_move).unwrap();
+        }
+    }
+
+    #[test]
+    fn new_game_is_empty() {
+        let game = Game::new();
+
+        assert_eq!(game.ply_count(), 0);
+        assert_eq!(game.get_result(), &GameStatus::Ongoing);
+        assert_eq!(game.get_board_state(), game.get_start_position());
+    }
+
+    #[test]
+    fn try_move_records_mover_capture_and_time() {
+        let mut game = Game::new();
+        let before = SystemTime::now();
+
+        play(&mut game, &[mv("e2", "e4"), mv("d7", "d5"), mv("e4", "d5")]);
+
+        let last = &game.get_moves()[2];
+        assert_eq!(game.ply_count(), 3);
+        assert_eq!(last.get_player(), &Player::White);
+        assert_eq!(last.captured_piece(), Some(Piece::new(PieceType::Pawn, Player::Black)));
+        assert!(last.timestamp >= before);
+        assert!(game.get_moves()[1].timestamp <= last.timestamp);
+    }
+
+    #[test]
+    fn illegal_moves_are_not_recorded() {
+        let mut game = Game::new();
+
+        assert_eq!(game.try_move(Player::Black, mv("e7", "e5")).unwrap_err(), MoveError::NotYourTurn);
+        assert_eq!(game.ply_count(), 0);
+    }
+
+    #[test]
+    fn position_at_every_ply() {
+        let mut game = Game::new();
+        let moves = [mv("e2", "e4"), mv("d7", "d5"), mv("e4", "d5"), mv("d8", "d5")];
+        play(&mut game, &moves);
+
+        let mut board_state = BoardState::new();
+        for ply in 0..=moves.len() {
+            assert_eq!(game.position_at_ply(ply).as_ref(), Some(&board_state));
+            if ply < moves.len() {
+                board_state.move_piece(moves[ply]);
+            }
+        }
+        assert_eq!(game.position_at_ply(moves.len()).as_ref(), Some(game.get_board_state()));
+        assert_eq!(game.position_at_ply(moves.len() + 1), None);
+    }
+
+    #[test]
+    fn from_fen_keeps_start_position() {
+        let fen = "4k3/8/8/8/8/8/8/4K2R w K - 0 1";
+        let mut game = Game::from_fen(fen).unwrap();
+
+        play(&mut game, &[mv("e1", "g1")]);
+
+        assert_eq!(game.get_start_position().to_fen(), fen);
+        assert_eq!(game.position_at_ply(0).unwrap().to_fen(), fen);
+        assert_eq!(Game::from_fen("8/8 w - - 0 1").unwrap_err(), FenError::WrongRankCount(2));
+    }
+
+    #[test]
+    fn metadata_set_and_replace() {
+        let mut game = Game::new();
+
+        game.set_metadata("White", "alice");
+        game.set_metadata("White", "bob");
+
+        assert_eq!(game.get_metadata("White"), Some("bob"));
+        assert_eq!(game.get_metadata("Black"), None);
+    }
+
+    #[test]
+    fn position_at_last_ply_keeps_resignation() {
+        let mut game = Game::new();
+        play(&mut game, &[mv("e2", "e4")]);
+
+        game.resign(Player::Black);
+
+        assert_eq!(game.position_at_ply(game.ply_count()).unwrap().get_game_status(), &GameStatus::WhiteWins);
+        assert_eq!(game.position_at_ply(0).unwrap().get_game_status(), &GameStatus::Ongoing);
+    }
+
+    #[test]
+    fn resign_sets_result() {
+        let mut game = Game::new();
+
+        game.resign(Player::White);
+
+        assert_eq!(game.get_result(), &GameStatus::BlackWins);
+    }
+
+    #[test]
+    fn to_pgn_uses_metadata_and_result() {
+        let mut game = Game::new();
+        game.set_metadata("White", "alice");
+        game.set_metadata("Black", "bob");
+        play(&mut game, &[mv("e2", "e4"), mv("e7", "e5")]);
+        game.resign(Player::Black);
+
+        let pgn = game.to_pgn(false).unwrap();
+
+        assert!(pgn.contains("[White \"alice\"]"));
+        assert!(pgn.contains("[Black \"bob\"]"));
+        assert!(pgn.ends_with("1. e4 e5 1-0\n"));
+    }
+
+    #[test]
+    fn to_pgn_from_set_up_position() {
+        let fen = "4k3/8/8/8/8/8/8/4K2R w K - 0 1";
+        let mut game = Game::from_fen(fen).unwrap();
+        play(&mut game, &[mv("e1", "g1")]);
+
+        let pgn = game.to_pgn(false).unwrap();
+
+        assert!(pgn.contains(&format!("[FEN \"{}\"]", fen)));
+        assert!(pgn.ends_with("1. O-O *\n"));
+    }
+
+    #[test]
+    fn to_pgn_under_other_rules() {
+        let rules = GameRules { repetition_mode: RepetitionMode::Never, ..GameRules::new() };
+        let mut game = Game::with_rules(rules);
+        let shuffle = [mv("g1", "f3"), mv("g8", "f6"), mv("f3", "g1"), mv("f6", "g8")];
+        for _ in 0..3 {
+            play(&mut game, &shuffle);
+        }
+
+        let pgn = game.to_pgn(false).unwrap();
+
+        assert_eq!(game.get_result(), &GameStatus::Ongoing);
+        assert!(pgn.ends_with("5. Nf3 Nf6 6. Ng1 Ng8 *\n"));
+    }
+
+    #[test]
+    fn to_pgn_under_other_rules_parses_back() {
+        let rules = GameRules { repetition_mode: RepetitionMode::Never, ..GameRules::new() };
+        let mut game = Game::with_rules(rules);
+        let shuffle = [mv("g1", "f3"), mv("g8", "f6"), mv("f3", "g1"), mv("f6", "g8")];
+        for _ in 0..3 {
+            play(&mut game, &shuffle);
+        }
+        let moves: Vec<StoredMove> = game.get_moves().iter().map(|played_move| *played_move.get_stored_move()).collect();
+
+        let text = game.to_pgn(false).unwrap();
+        let pgn = Pgn::parse_with_rules(&text, rules).unwrap();
+
+        assert_eq!(pgn.get_moves(), &moves[..]);
+        assert_eq!(pgn.replay().unwrap().get_game_status(), &GameStatus::Ongoing);
+        // under the default rules the third time the start position comes round ends the game
+        assert_eq!(Pgn::parse(&text).unwrap_err(), PgnError::IllegalMove { ply: 9, san: String::from("Nf3") });
+    }
+}
@@ -0,0 +1,54 @@
+use std::time::SystemTime;
+
+use crate::board_state::{ MoveRecord, Piece, Player, StoredMove };
+
+// One move of a Game, as it was played and when
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct PlayedMove {
+    pub record: MoveRecord,
+    pub timestamp: SystemTime,
+}
+
+impl PlayedMove {
+    pub fn get_stored_move(&self) -> &StoredMove {
+        &self.record.stored_move
+    }
+
+    pub fn get_player(&self) -> &Player {
+        &self.record.player
+    }
+
+    pub fn captured_piece(&self) -> Option<Piece> {
+        self.record.captured_piece()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board_state::square::sq;
+    use crate::board_state::PieceType;
+
+    #[test]
+    fn getters_read_the_record() {
+        let stored_move = StoredMove {
+            start_pos: sq("e4"),
+            end_pos: sq("d5"),
+            promotion: None,
+        };
+        let captured = Piece::new(PieceType::Pawn, Player::Black);
+        let played_move = PlayedMove {
+            record: MoveRecord {
+                stored_move,
+                player: Player::White,
+                moved_piece: Piece::new(PieceType::Pawn, Player::White),
+                captured: Some((captured, stored_move.end_pos)),
+            },
+            timestamp: SystemTime::UNIX_EPOCH,
+        };
+
+        assert_eq!(played_move.get_stored_move(), &stored_move);
+        assert_eq!(played_move.get_player(), &Player::White);
+        assert_eq!(played_move.captured_piece(), Some(captured));
+    }
+}
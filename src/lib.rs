@@ -1,10 +1,14 @@
 pub mod board_state;
 pub mod move_generation;
+pub mod pgn;
+pub mod game;
+pub mod perft;
+pub mod rng;
+pub mod agent;
+pub mod belief;
 
 // TODO - remove #[derive()] if possible (likely will be possible for debug)
 
-// TODO implement a list of previous moves?
-
 // TODO next thing I should do is just consider how my code should be organized
 
 // these are the two overarching modules in the code:
@@ -22,6 +26,15 @@ pub mod move_generation;
 // and generate legal moves for a specific tile given the board state and the tile, or the pos
 // all this can do is generate moves. it sits without any information itself, it just has static functions
 
+// pgn sits on top of both, writing and reading whole games as Portable Game Notation
+
+// game wraps the board state with the list of previous moves, the start position and metadata,
+// so everything that needs the history of a game reads it from one place
+
+// agent is the interface for bots, which only ever see a player's fog of war view
+
+// belief follows one player's fog of war views through a game to track where the opponent's pieces could be
+
 // TODO To this end, I will go through from player to board_state first, adding tests for each function and rethinking
 // each function. i think all the functionality is more or less there, but i want to be able to hide things if possible
 // and also maybe even optimize a bit
\ No newline at end of file
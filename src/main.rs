@@ -1,4 +1,8 @@
+use std::env;
+use std::process;
+
 use dark_chess_server::board_state::BoardState;
+use dark_chess_server::perft;
 // use dark_chess_server::board_state::Player;
 // use dark_chess_server::board_state::Tile;
 // use dark_chess_server::board_state::{ Piece, PieceType };
@@ -6,7 +10,54 @@ use dark_chess_server::board_state::BoardState;
 
 // use dark_chess_server::move_generation::MoveGeneration;
 
+const USAGE: &str = "usage: dark_chess_server [perft|divide <depth> [fen]]";
+
 fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    match args.first().map(|arg| arg.as_str()) {
+        Some("perft") => {
+            let (board, depth) = perft_args(&args[1..]);
+            println!("{}", perft::perft(&board, depth));
+        },
+        Some("divide") => {
+            let (board, depth) = perft_args(&args[1..]);
+            let divided = perft::divide(&board, depth);
+
+            for (stored_move, nodes) in divided.iter() {
+                println!("{}: {}", stored_move, nodes);
+            }
+            println!();
+            println!("moves: {}", divided.len());
+            println!("nodes: {}", divided.iter().map(|(_, nodes)| nodes).sum::<u64>());
+        },
+        Some(_) => exit_with(USAGE),
+        None => print_board(),
+    }
+}
+
+// the depth, then optionally a FEN to start from, which may be given as several arguments
+fn perft_args(args: &[String]) -> (BoardState, usize) {
+    let depth = match args.first().map(|arg| arg.parse()) {
+        Some(Ok(depth)) => depth,
+        _ => exit_with(USAGE),
+    };
+
+    let board = if args.len() > 1 {
+        BoardState::from_fen(&args[1..].join(" ")).unwrap_or_else(|error| exit_with(&error.to_string()))
+    } else {
+        BoardState::new()
+    };
+
+    (board, depth)
+}
+
+fn exit_with(message: &str) -> ! {
+    eprintln!("{}", message);
+    process::exit(1);
+}
+
+fn print_board() {
     let board = BoardState::new();
 
     // println!("Board before move:\n{}", board.display_full_board_utf());
@@ -27,4 +78,3 @@ fn main() {
 
     // MoveGeneration::generate_moves_for_tile(&board, tile);
 }
-
@@ -1,145 +1,99 @@
-use crate::board_state::{ BoardState, Tile, StoredMove, PieceType };
+mod attacks;
 
-// this is a struct to make parsing all possible moves easier 
-struct Direction {
-    pub up: i32,
-    pub right: i32,
-}
+use crate::board_state::{ BoardState, Bitboards, Tile, StoredMove, Piece, PieceType, Player, Square };
+use crate::move_generation::attacks::{
+    BISHOP_DIRS, B_PAWN_DIRS, KING_ATTACKS, KING_QUEEN_DIRS, KNIGHT_ATTACKS, KNIGHT_STEPS, ROOK_DIRS, STEPS, W_PAWN_DIRS,
+};
 
-const UP: Direction = Direction{ up: 1, right: 0 };
-const UP_RIGHT: Direction = Direction{ up: 1, right: 1 };
-const RIGHT: Direction = Direction{ up: 0, right: 1 };
-const DOWN_RIGHT: Direction = Direction{ up: -1, right: 1 };
-const DOWN: Direction = Direction{ up: -1, right: 0 };
-const DOWN_LEFT: Direction = Direction{ up: -1, right: -1 };
-const LEFT: Direction = Direction{ up: 0, right: -1 };
-const UP_LEFT: Direction = Direction{ up: 1, right: -1 };
-
-const KING_QUEEN_DIRS: [Direction; 8] = [UP, UP_RIGHT, RIGHT, DOWN_RIGHT, DOWN, DOWN_LEFT, LEFT, UP_LEFT];
-const BISHOP_DIRS: [Direction; 4] = [UP_RIGHT, DOWN_RIGHT, DOWN_LEFT, UP_LEFT];
-const ROOK_DIRS: [Direction; 4] = [UP, RIGHT, DOWN, LEFT];
-
-const KNIGHT_DIRS: [Direction; 8] = [
-    Direction{up: 2, right: 1},
-    Direction{up: 1, right: 2},
-    Direction{up: -1, right: 2},
-    Direction{up: -2, right: 1},
-    Direction{up: -2, right: -1},
-    Direction{up: -1, right: -2},
-    Direction{up: 1, right: -2},
-    Direction{up: 2, right: -1}
-];
-
-const B_PAWN_DIRS: [Direction; 3] = [UP, UP_RIGHT, UP_LEFT];
-
-const W_PAWN_DIRS: [Direction; 3] = [DOWN, DOWN_RIGHT, DOWN_LEFT];
+// the pieces a pawn can become once it reaches the last row
+const PROMOTION_TYPES: [PieceType; 4] = [PieceType::Queen, PieceType::Rook, PieceType::Bishop, PieceType::Knight];
 
 pub struct MoveGeneration {}
 
-// TODO tests! most of these functions are untested, but i believe should work
-// for similar gen_moves_ functions, there is probably a good way to deal with that
+// moves are generated from the bitboards in BoardState and the tables in attacks.rs.
+// within a piece, moves come in the order of its dirs, nearest square first along each ray
+// the generated moves are checked as a whole by the perft node counts in perft.rs
 impl MoveGeneration {
-    // checks that a dir is in bounds
-    fn within_bounds(dir: &Direction, tile: &Tile) -> bool {
-        // uses add_dir instead of doing it quicker manually
-        // for easier readability
-        let (x, y) = MoveGeneration::add_dir(dir, tile);
-
-        // implicitly guaranteed to be >= 0 since x and y are unsigned integers
-        x < 8 && y < 8
-    }
-
-    // Adds a direction to a tile
-    // TODO integer overflow (underflow?) possible error
-    // example: dir = {-1, -1}, tile at (0, 0) would return (255, 255)
-    // also demonstrates the need to provide testing to this module
-    fn add_dir(dir: &Direction, tile: &Tile) -> (usize, usize) {
-        let (x, y) = tile.get_pos();
-
-        // TODO conversion in rust is (rightfully) harder than i expected
-        // for now I am using the as keyword because I know theoretically that
-        // this should never fail, but in the future I should implement some
-        // kind of function that does error handling in some way
-        let x = *x as i32;
-        let y = *y as i32;
-
-        let x = x + dir.right;
-        let y = y + dir.up;
-
-        let x = x as usize;
-        let y = y as usize;
-
-        (x, y)
-    }
-
-    // TODO could probably turn into one line, but the more verbose implementation i think is easier to read?
-    // checks a direction, and determines if there is a piece there,
-    // or if it is out of bounds
-    fn valid_dir(dir: &Direction, tile: &Tile, board_state: &BoardState) -> bool {
-        // first checks move is within bounds
-        if !MoveGeneration::within_bounds(dir, tile) {
-            return false;
-        }
-
-        let (x, y) = MoveGeneration::add_dir(dir, tile);
+    // the squares of the player's own pieces, which no move can land on
+    fn own_pieces(tile: &Tile, board_state: &BoardState) -> u64 {
+        board_state.get_bitboards().get_player_pieces(*tile.get_piece().unwrap().get_player())
+    }
 
-        let target_tile = board_state.get_tile_at_pos((x, y));
+    // adds a move to each square in targets, visiting them nearest first along dir
+    fn add_ray_moves(moves: &mut Vec<StoredMove>, tile: &Tile, dir: usize, mut targets: u64) {
+        while targets != 0 {
+            let index = if attacks::increasing(dir) {
+                targets.trailing_zeros()
+            } else {
+                63 - targets.leading_zeros()
+            };
+            targets &= !(1 << index);
+
+            moves.push(StoredMove {
+                start_pos: *tile.get_pos(),
+                end_pos: Square::from_index(index as usize).unwrap(),
+                promotion: None,
+            });
+        }
+    }
 
-        // TODO use of unwrap should probably be removed
-        match *target_tile.get_piece() {
-            Some(piece) => piece.get_player() != tile.get_piece().unwrap().get_player(),
-            None => true
+    // adds a move for each single step that lands in targets, in the order of the steps
+    fn add_step_moves(moves: &mut Vec<StoredMove>, tile: &Tile, steps: &[[u64; 64]; 8], targets: u64) {
+        let index = tile.get_pos().index();
+
+        for step in steps {
+            if step[index] & targets != 0 {
+                moves.push(StoredMove {
+                    start_pos: *tile.get_pos(),
+                    end_pos: Square::from_index(step[index].trailing_zeros() as usize).unwrap(),
+                    promotion: None,
+                });
+            }
         }
     }
 
-    // if the spot is empty, or contains empty piece, returns move to this spot
-    fn check_dir(dir: &Direction, tile: &Tile, board_state: &BoardState) -> Option<StoredMove> {
-        if MoveGeneration::valid_dir(dir, tile, board_state) {
-            let (x, y) = MoveGeneration::add_dir(dir, tile);
+    // walks every ray for rooks, bishops and queens. each ray runs up to the first piece in the way,
+    // including it if it is an enemy
+    fn gen_moves_sliding(dirs: &[usize], tile: &Tile, board_state: &BoardState) -> Vec<StoredMove> {
+        let occupied = board_state.get_bitboards().get_occupied();
+        let own_pieces = MoveGeneration::own_pieces(tile, board_state);
+        let mut moves = Vec::new();
 
-            return Some(StoredMove{ 
-                start_pos: *tile.get_pos(),
-                end_pos: (x, y),
-                promotion: None,
-            })
-        } else {
-            return None
+        for dir in dirs {
+            let targets = attacks::sliding_attacks(*dir, tile.get_pos().index(), occupied) & !own_pieces;
+            MoveGeneration::add_ray_moves(&mut moves, tile, *dir, targets);
         }
+        moves
     }
 
-    // TODO could likely be cleaned
-    // check_dir but recurses in that direction
-    fn continue_check_dir(dir: &Direction, tile: &Tile, board_state: &BoardState) -> Vec<StoredMove> {
+    // every move player can make, including castles, en passants and promotions
+    pub fn gen_moves_for_player(board_state: &BoardState, player: Player) -> Vec<StoredMove> {
         let mut moves = Vec::new();
-        
-        match MoveGeneration::check_dir(dir, tile, board_state) {
-            Some(stored_move) => moves.push(stored_move),
-            None => ()
-        };
-            
-        let mut continued_moves = match board_state.get_tile_at_pos(*tile.get_pos()).get_piece() {
-            Some(piece) => Vec::new(),
-            None => MoveGeneration::continue_check_dir(
-                &Direction {
-                    up: dir.up + dir.up, 
-                    right: dir.right + dir.right
-                }, 
-                tile,
-                board_state
-            )
-        };
 
-        moves.append(&mut continued_moves);
+        for pos in Bitboards::squares(board_state.get_bitboards().get_player_pieces(player)) {
+            moves.append(&mut MoveGeneration::gen_moves_tile(board_state.get_tile_at_pos(pos), board_state));
+        }
 
         moves
     }
 
-    pub fn gen_moves_for_player(board_state: &BoardState, player: Player) -> Vec<StoredMove> {
-        // TODO
-        Vec::new()
+    // the moves for the piece at pos, useful for showing where a single piece can go
+    pub fn gen_moves_for_pos(board_state: &BoardState, pos: Square) -> Vec<StoredMove> {
+        MoveGeneration::gen_moves_tile(board_state.get_tile_at_pos(pos), board_state)
     }
 
-    fn gen_moves_tile(tile: &Tile, board_state: &BoardState) -> Vec<StoredMove> {
+    // there are no moves once the game is over
+    pub fn gen_moves_tile(tile: &Tile, board_state: &BoardState) -> Vec<StoredMove> {
+        if board_state.get_game_status().is_over() {
+            return Vec::new();
+        }
+
+        MoveGeneration::gen_reachable_tile(tile, board_state)
+    }
+
+    // the moves the piece on tile could make whether or not the game is over
+    // visibility is built from these, so the fog does not change when the game ends
+    pub(crate) fn gen_reachable_tile(tile: &Tile, board_state: &BoardState) -> Vec<StoredMove> {
         match tile.get_piece() {
             Some(piece) => match piece.get_piece_type() {
                 PieceType::King => MoveGeneration::gen_moves_king(tile, board_state),
@@ -153,10 +107,6 @@ impl MoveGeneration {
         }
     }
 
-    // TODO this and knight are nearly identical
-    // i think i want them to be seperate functions though,
-    // i think it makes my code more immediately readable and understandable
-    // might be able to seperate more into functions
     fn gen_moves_king(tile: &Tile, board_state: &BoardState) -> Vec<StoredMove> {
         // panicking if piece is not of piecetype king, since it should always be so
         if tile.get_piece().unwrap().get_piece_type() != &PieceType::King {
@@ -164,47 +114,68 @@ impl MoveGeneration {
         }
         let mut moves = Vec::new();
 
-        // TODO map this instead of for?
-        for potential_dir in KING_QUEEN_DIRS {
-            match MoveGeneration::check_dir(&potential_dir, tile, board_state) {
-                Some(stored_move) => moves.push(stored_move),
-                None => ()
+        let targets = KING_ATTACKS[tile.get_pos().index()] & !MoveGeneration::own_pieces(tile, board_state);
+        MoveGeneration::add_step_moves(&mut moves, tile, &STEPS, targets);
+
+        moves.append(&mut MoveGeneration::gen_moves_castle(tile, board_state));
+
+        moves
+    }
+
+    // a castle only needs the right to still be held, the rook to be in its corner and the tiles
+    // between king and rook to be empty. since this is dark chess, castling out of, through or
+    // into an attacked tile is allowed, the same way moving into check is allowed
+    fn gen_moves_castle(tile: &Tile, board_state: &BoardState) -> Vec<StoredMove> {
+        let player = *tile.get_piece().unwrap().get_player();
+        let castling_rights = board_state.get_castling_rights();
+        let bitboards = board_state.get_bitboards();
+        let mut moves = Vec::new();
+
+        let on_rank = |file| Square::new(file, player.back_rank()).unwrap();
+        if tile.get_pos() != &on_rank(4) {
+            return moves;
+        }
+
+        // (right held, rook file, files that must be empty, king destination file)
+        let castles = [
+            (castling_rights.can_castle_kingside(player), 7, &[5, 6][..], 6),
+            (castling_rights.can_castle_queenside(player), 0, &[1, 2, 3][..], 2),
+        ];
+
+        for (allowed, rook_file, between, end_file) in castles {
+            let rook_in_place = bitboards.get_pieces(Piece::new(PieceType::Rook, player))
+                & Bitboards::bit(on_rank(rook_file)) != 0;
+            let between_bits = between.iter().fold(0, |bits, file| bits | Bitboards::bit(on_rank(*file)));
+            let path_clear = bitboards.get_occupied() & between_bits == 0;
+
+            if allowed && rook_in_place && path_clear {
+                moves.push(StoredMove {
+                    start_pos: *tile.get_pos(),
+                    end_pos: on_rank(end_file),
+                    promotion: None,
+                });
             }
         }
 
         moves
     }
 
-    // TODO this is very similar to bishop and rook
     fn gen_moves_queen(tile: &Tile, board_state: &BoardState) -> Vec<StoredMove> {
         // panicking if piece is not of piecetype queen, since it should always be so
         if tile.get_piece().unwrap().get_piece_type() != &PieceType::Queen {
             panic!("Given the wrong piece type. Piece type given was: {:?}", tile.get_piece().unwrap().get_piece_type())
         }
-        let mut moves = Vec::new();
-
-        for potential_dir in KING_QUEEN_DIRS {
-            let mut continued_moves = MoveGeneration::continue_check_dir(&potential_dir, tile, board_state);
 
-            moves.append(&mut continued_moves);
-        }
-        moves
+        MoveGeneration::gen_moves_sliding(&KING_QUEEN_DIRS, tile, board_state)
     }
 
-    // TODO this is very similar to queen and bishop
     fn gen_moves_rook(tile: &Tile, board_state: &BoardState) -> Vec<StoredMove> {
         // panicking if piece is not of piecetype rook, since it should always be so
         if tile.get_piece().unwrap().get_piece_type() != &PieceType::Rook {
             panic!("Given the wrong piece type. Piece type given was: {:?}", tile.get_piece().unwrap().get_piece_type())
         }
-        let mut moves = Vec::new();
 
-        for potential_dir in ROOK_DIRS {
-            let mut continued_moves = MoveGeneration::continue_check_dir(&potential_dir, tile, board_state);
-            
-            moves.append(&mut continued_moves);
-        }
-        moves
+        MoveGeneration::gen_moves_sliding(&ROOK_DIRS, tile, board_state)
     }
 
     // very similar to king
@@ -215,160 +186,553 @@ impl MoveGeneration {
         }
         let mut moves = Vec::new();
 
-        // TODO map this instead of for?
-        for potential_dir in KNIGHT_DIRS {
-            match MoveGeneration::check_dir(&potential_dir, tile, board_state) {
-                Some(stored_move) => moves.push(stored_move),
-                None => ()
-            }
-        }
+        let targets = KNIGHT_ATTACKS[tile.get_pos().index()] & !MoveGeneration::own_pieces(tile, board_state);
+        MoveGeneration::add_step_moves(&mut moves, tile, &KNIGHT_STEPS, targets);
 
         moves
     }
 
-    // TODO this is very similar to rook and queen
     fn gen_moves_bishop(tile: &Tile, board_state: &BoardState) -> Vec<StoredMove> {
         // panicking if piece is not of piecetype bishop, since it should always be so
         if tile.get_piece().unwrap().get_piece_type() != &PieceType::Bishop {
             panic!("Given the wrong piece type. Piece type given was: {:?}", tile.get_piece().unwrap().get_piece_type())
         }
+
+        MoveGeneration::gen_moves_sliding(&BISHOP_DIRS, tile, board_state)
+    }
+
+    // the first pawn dir is the single step forward, the other two are the diagonal captures
+    fn gen_moves_pawn(tile: &Tile, board_state: &BoardState) -> Vec<StoredMove> {
+        // panicking if piece is not of piecetype pawn, since it should always be so
+        if tile.get_piece().unwrap().get_piece_type() != &PieceType::Pawn {
+            panic!("Given the wrong piece type. Piece type given was: {:?}", tile.get_piece().unwrap().get_piece_type())
+        }
+        let player = *tile.get_piece().unwrap().get_player();
+        let enemy = player.opponent();
+        let index = tile.get_pos().index();
+        let bitboards = board_state.get_bitboards();
+        let empty = !bitboards.get_occupied();
         let mut moves = Vec::new();
 
-        for potential_dir in BISHOP_DIRS {
-            let mut continued_moves = MoveGeneration::continue_check_dir(&potential_dir, tile, board_state);
-            
-            moves.append(&mut continued_moves);
+        let pawn_dirs = match player {
+            Player::White => &W_PAWN_DIRS,
+            Player::Black => &B_PAWN_DIRS,
+        };
+        let forward = STEPS[pawn_dirs[0]];
+
+        // pawns can only step forward onto empty tiles
+        let single_step = forward[index] & empty;
+        if single_step != 0 {
+            let end_index = single_step.trailing_zeros() as usize;
+            MoveGeneration::add_pawn_move(&mut moves, tile, end_index, player);
+
+            // the two step is only allowed from the start rank, and only if both tiles are empty
+            if tile.get_pos().get_rank() == player.pawn_rank() && forward[end_index] & empty != 0 {
+                MoveGeneration::add_pawn_move(&mut moves, tile, forward[end_index].trailing_zeros() as usize, player);
+            }
+        }
+
+        // pawns can only move diagonally when capturing an enemy piece, or capturing en passant
+        let captures = bitboards.get_player_pieces(enemy) | MoveGeneration::en_passant_bit(enemy, board_state);
+        for capture_dir in &pawn_dirs[1..] {
+            let capture = STEPS[*capture_dir][index] & captures;
+            if capture != 0 {
+                MoveGeneration::add_pawn_move(&mut moves, tile, capture.trailing_zeros() as usize, player);
+            }
         }
+
         moves
     }
 
-    // TODO pawn move gen
-    fn gen_moves_pawn(tile: &Tile, board_state: &BoardState) -> Vec<StoredMove> {
-        Vec::new()
+    // the en passant tile as a bit, if the pawn which skipped it belongs to enemy and is still there
+    fn en_passant_bit(enemy: Player, board_state: &BoardState) -> u64 {
+        let enemy_pawns = board_state.get_bitboards().get_pieces(Piece::new(PieceType::Pawn, enemy));
+        match (board_state.get_en_passant(), board_state.get_en_passant_pawn_pos()) {
+            (Some(pos), Some(pawn_pos)) if enemy_pawns & Bitboards::bit(pawn_pos) != 0 => Bitboards::bit(*pos),
+            _ => 0
+        }
     }
 
-    // TODO gen all moves for one side
+    // adds a pawn move, or one move per promotion choice if the pawn reaches the last rank
+    fn add_pawn_move(moves: &mut Vec<StoredMove>, tile: &Tile, end_index: usize, player: Player) {
+        let end_pos = Square::from_index(end_index).unwrap();
+
+        // the last rank is where the opponent's pieces start
+        if end_pos.get_rank() == player.opponent().back_rank() {
+            for piece_type in PROMOTION_TYPES {
+                moves.push(StoredMove {
+                    start_pos: *tile.get_pos(),
+                    end_pos,
+                    promotion: Some(Piece::new(piece_type, player)),
+                });
+            }
+        } else {
+            moves.push(StoredMove {
+                start_pos: *tile.get_pos(),
+                end_pos,
+                promotion: None,
+            });
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::board_state::square::sq;
+
     use crate::board_state::{PieceType, Player};
 
+    fn pawn_tile(board_state: &mut BoardState, player: Player, pos: Square) -> Tile {
+        board_state.set_piece_at_pos(Some(Piece::new(PieceType::Pawn, player)), pos);
+        *board_state.get_tile_at_pos(pos)
+    }
+
+    fn end_positions(moves: &[StoredMove]) -> Vec<Square> {
+        moves.iter().map(|stored_move| stored_move.end_pos).collect()
+    }
+
+    #[test]
+    fn pawn_white_start_single_and_double_step() {
+        let board_state = BoardState::new();
+        let tile = board_state.get_tile_at_pos(sq("e2"));
+
+        let moves = MoveGeneration::gen_moves_pawn(tile, &board_state);
+
+        assert_eq!(end_positions(&moves), vec![sq("e3"), sq("e4")]);
+    }
+
+    #[test]
+    fn pawn_black_start_single_and_double_step() {
+        let board_state = BoardState::new();
+        let tile = board_state.get_tile_at_pos(sq("d7"));
+
+        let moves = MoveGeneration::gen_moves_pawn(tile, &board_state);
+
+        assert_eq!(end_positions(&moves), vec![sq("d6"), sq("d5")]);
+    }
+
+    #[test]
+    fn pawn_no_double_step_off_start_row() {
+        let mut board_state = BoardState::empty();
+        let tile = pawn_tile(&mut board_state, Player::White, sq("e3"));
+
+        let moves = MoveGeneration::gen_moves_pawn(&tile, &board_state);
+
+        assert_eq!(end_positions(&moves), vec![sq("e4")]);
+    }
+
+    #[test]
+    fn pawn_blocked_forward() {
+        let mut board_state = BoardState::empty();
+        let tile = pawn_tile(&mut board_state, Player::White, sq("e2"));
+        board_state.set_piece_at_pos(Some(Piece::new(PieceType::Knight, Player::Black)), sq("e3"));
+
+        let moves = MoveGeneration::gen_moves_pawn(&tile, &board_state);
+
+        assert!(moves.is_empty());
+    }
+
+    #[test]
+    fn pawn_double_step_blocked_on_second_tile() {
+        let mut board_state = BoardState::empty();
+        let tile = pawn_tile(&mut board_state, Player::Black, sq("c7"));
+        board_state.set_piece_at_pos(Some(Piece::new(PieceType::Knight, Player::Black)), sq("c5"));
+
+        let moves = MoveGeneration::gen_moves_pawn(&tile, &board_state);
+
+        assert_eq!(end_positions(&moves), vec![sq("c6")]);
+    }
+
+    #[test]
+    fn pawn_captures_only_enemies() {
+        let mut board_state = BoardState::empty();
+        let tile = pawn_tile(&mut board_state, Player::White, sq("e4"));
+        board_state.set_piece_at_pos(Some(Piece::new(PieceType::Knight, Player::Black)), sq("d5"));
+        board_state.set_piece_at_pos(Some(Piece::new(PieceType::Knight, Player::White)), sq("f5"));
+
+        let moves = MoveGeneration::gen_moves_pawn(&tile, &board_state);
+
+        assert_eq!(end_positions(&moves), vec![sq("e5"), sq("d5")]);
+    }
+
+    #[test]
+    fn pawn_no_diagonal_onto_empty() {
+        let mut board_state = BoardState::empty();
+        let tile = pawn_tile(&mut board_state, Player::Black, sq("a4"));
+
+        let moves = MoveGeneration::gen_moves_pawn(&tile, &board_state);
+
+        assert_eq!(end_positions(&moves), vec![sq("a3")]);
+    }
+
+    #[test]
+    fn pawn_white_promotion() {
+        let mut board_state = BoardState::empty();
+        let tile = pawn_tile(&mut board_state, Player::White, sq("a7"));
+
+        let moves = MoveGeneration::gen_moves_pawn(&tile, &board_state);
+
+        let promotions: Vec<Option<Piece>> = moves.iter().map(|stored_move| stored_move.promotion).collect();
+        assert_eq!(end_positions(&moves), vec![sq("a8"); 4]);
+        assert_eq!(promotions, vec![
+            Some(Piece::new(PieceType::Queen, Player::White)),
+            Some(Piece::new(PieceType::Rook, Player::White)),
+            Some(Piece::new(PieceType::Bishop, Player::White)),
+            Some(Piece::new(PieceType::Knight, Player::White)),
+        ]);
+    }
+
+    #[test]
+    fn pawn_black_capture_promotion() {
+        let mut board_state = BoardState::empty();
+        let tile = pawn_tile(&mut board_state, Player::Black, sq("g2"));
+        board_state.set_piece_at_pos(Some(Piece::new(PieceType::Rook, Player::White)), sq("g1"));
+        board_state.set_piece_at_pos(Some(Piece::new(PieceType::Rook, Player::White)), sq("h1"));
+
+        let moves = MoveGeneration::gen_moves_pawn(&tile, &board_state);
+
+        assert_eq!(moves.len(), 4);
+        assert!(moves.iter().all(|stored_move| stored_move.end_pos == sq("h1")));
+        assert!(moves.iter().all(|stored_move| stored_move.promotion.unwrap().get_player() == &Player::Black));
+    }
+
+    // clears the pieces between the kings and rooks of the starting position
+    fn castle_board() -> BoardState {
+        let mut board_state = BoardState::new();
+        for file in [1, 2, 3, 5, 6] {
+            board_state.set_piece_at_pos(None, Square::new(file, 0).unwrap());
+            board_state.set_piece_at_pos(None, Square::new(file, 7).unwrap());
+        }
+        board_state
+    }
+
+    fn castle_end_positions(board_state: &BoardState, pos: Square) -> Vec<Square> {
+        let tile = board_state.get_tile_at_pos(pos);
+        end_positions(&MoveGeneration::gen_moves_castle(tile, board_state))
+    }
+
+    #[test]
+    fn castle_both_sides_white() {
+        let board_state = castle_board();
+
+        assert_eq!(castle_end_positions(&board_state, sq("e1")), vec![sq("g1"), sq("c1")]);
+    }
+
+    #[test]
+    fn castle_both_sides_black() {
+        let board_state = castle_board();
+
+        assert_eq!(castle_end_positions(&board_state, sq("e8")), vec![sq("g8"), sq("c8")]);
+    }
+
+    #[test]
+    fn castle_included_in_king_moves() {
+        let board_state = castle_board();
+        let tile = board_state.get_tile_at_pos(sq("e1"));
+
+        let moves = MoveGeneration::gen_moves_king(tile, &board_state);
+
+        assert!(end_positions(&moves).contains(&sq("g1")));
+        assert!(end_positions(&moves).contains(&sq("c1")));
+    }
+
+    #[test]
+    fn castle_blocked_by_piece_between() {
+        let mut board_state = castle_board();
+        board_state.set_piece_at_pos(Some(Piece::new(PieceType::Knight, Player::White)), sq("b1"));
+        board_state.set_piece_at_pos(Some(Piece::new(PieceType::Knight, Player::Black)), sq("f1"));
+
+        assert!(castle_end_positions(&board_state, sq("e1")).is_empty());
+    }
+
+    #[test]
+    fn castle_not_from_starting_position() {
+        let board_state = BoardState::new();
+
+        assert!(castle_end_positions(&board_state, sq("e1")).is_empty());
+    }
+
     #[test]
-    fn within_bounds_x_too_large() {
-        let dir = Direction{ right: 1, up: 0 };
-        let pos = (7, 4);
-        let tile = Tile::new(None, pos);
+    fn castle_needs_rook_in_corner() {
+        let mut board_state = castle_board();
+        board_state.set_piece_at_pos(None, sq("h1"));
 
-        assert!(!MoveGeneration::within_bounds(&dir, &tile));
+        assert_eq!(castle_end_positions(&board_state, sq("e1")), vec![sq("c1")]);
     }
 
     #[test]
-    fn within_bounds_y_too_large() {
-        let dir = Direction{ right: 0, up: 1 };
-        let pos = (4, 7);
-        let tile = Tile::new(None, pos);
+    fn castle_needs_rights() {
+        let mut board_state = castle_board();
+        board_state.move_piece(StoredMove { start_pos: sq("h1"), end_pos: sq("g1"), promotion: None });
+        board_state.move_piece(StoredMove { start_pos: sq("g1"), end_pos: sq("h1"), promotion: None });
 
-        assert!(!MoveGeneration::within_bounds(&dir, &tile));
+        assert_eq!(castle_end_positions(&board_state, sq("e1")), vec![sq("c1")]);
     }
 
+    // in dark chess, the king may move into check, so castling through or into
+    // an attacked tile is allowed as well
     #[test]
-    fn within_bounds_x_too_small() {
-        let dir = Direction{ right: -1, up: 0 };
-        let pos = (0, 4);
-        let tile = Tile::new(None, pos);
+    fn castle_through_attacked_tile_allowed() {
+        let mut board_state = castle_board();
+        board_state.set_piece_at_pos(None, sq("f2"));
+        board_state.set_piece_at_pos(Some(Piece::new(PieceType::Rook, Player::Black)), sq("f5"));
 
-        assert!(!MoveGeneration::within_bounds(&dir, &tile));
+        assert_eq!(castle_end_positions(&board_state, sq("e1")), vec![sq("g1"), sq("c1")]);
     }
 
     #[test]
-    fn within_bounds_y_too_small() {
-        let dir = Direction{ right: 0, up: -1 };
-        let pos = (4, 0);
-        let tile = Tile::new(None, pos);
+    fn castle_into_attacked_tile_allowed() {
+        let mut board_state = castle_board();
+        board_state.set_piece_at_pos(None, sq("g2"));
+        board_state.set_piece_at_pos(Some(Piece::new(PieceType::Rook, Player::Black)), sq("g5"));
 
-        assert!(!MoveGeneration::within_bounds(&dir, &tile));
+        assert_eq!(castle_end_positions(&board_state, sq("e1")), vec![sq("g1"), sq("c1")]);
     }
 
     #[test]
-    fn within_bounds_x_y_big() {
-        let dir = Direction{ up: 1, right: 1 };
-        let pos = (7, 7);
-        let tile = Tile::new(None, pos);
+    fn castle_out_of_check_allowed() {
+        let mut board_state = castle_board();
+        board_state.set_piece_at_pos(None, sq("e2"));
+        board_state.set_piece_at_pos(Some(Piece::new(PieceType::Rook, Player::Black)), sq("e5"));
 
-        assert!(!MoveGeneration::within_bounds(&dir, &tile));
+        assert_eq!(castle_end_positions(&board_state, sq("e1")), vec![sq("g1"), sq("c1")]);
     }
 
     #[test]
-    fn within_bounds_x_y_small() {
-        let dir = Direction{ up: -1, right: -1 };
-        let pos = (0, 0);
-        let tile = Tile::new(None, pos);
+    fn pawn_en_passant_capture() {
+        let mut board_state = BoardState::new();
+        board_state.set_piece_at_pos(Some(Piece::new(PieceType::Pawn, Player::Black)), sq("d4"));
+        board_state.move_piece(StoredMove { start_pos: sq("e2"), end_pos: sq("e4"), promotion: None });
+        let tile = board_state.get_tile_at_pos(sq("d4"));
+
+        let moves = MoveGeneration::gen_moves_pawn(tile, &board_state);
+
+        assert_eq!(end_positions(&moves), vec![sq("d3"), sq("e3")]);
+    }
 
-        assert!(!MoveGeneration::within_bounds(&dir, &tile));
-    }  
-    
     #[test]
-    fn within_bounds_x_y_big_small() {
-        let dir = Direction{ right: 1, up: -1 };
-        let pos = (7, 0);
-        let tile = Tile::new(None, pos);
+    fn pawn_en_passant_only_right_after_two_step() {
+        let mut board_state = BoardState::new();
+        board_state.set_piece_at_pos(Some(Piece::new(PieceType::Pawn, Player::White)), sq("f5"));
+        board_state.move_piece(StoredMove { start_pos: sq("e7"), end_pos: sq("e5"), promotion: None });
+        board_state.move_piece(StoredMove { start_pos: sq("b1"), end_pos: sq("c3"), promotion: None });
+        let tile = board_state.get_tile_at_pos(sq("f5"));
+
+        let moves = MoveGeneration::gen_moves_pawn(tile, &board_state);
 
-        assert!(!MoveGeneration::within_bounds(&dir, &tile));
-    } 
+        assert_eq!(end_positions(&moves), vec![sq("f6")]);
+    }
 
     #[test]
-    fn within_bounds_x_y_small_big() {
-        let dir = Direction{ right: -1, up: 1 };
-        let pos = (0, 7);
-        let tile = Tile::new(None, pos);
+    fn pawn_en_passant_not_for_non_adjacent() {
+        let mut board_state = BoardState::new();
+        board_state.set_piece_at_pos(Some(Piece::new(PieceType::Pawn, Player::White)), sq("g5"));
+        board_state.move_piece(StoredMove { start_pos: sq("e7"), end_pos: sq("e5"), promotion: None });
+        let tile = board_state.get_tile_at_pos(sq("g5"));
+
+        let moves = MoveGeneration::gen_moves_pawn(tile, &board_state);
 
-        assert!(!MoveGeneration::within_bounds(&dir, &tile));
-    } 
+        assert_eq!(end_positions(&moves), vec![sq("g6")]);
+    }
 
     #[test]
-    fn within_bounds_x_close_big() {
-        let dir = Direction{ right: 1, up: 0 };
-        let pos = (6, 4);
-        let tile = Tile::new(None, pos);
+    fn pawn_en_passant_only_takes_pawns() {
+        let mut board_state = BoardState::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1").unwrap();
+        board_state.set_piece_at_pos(Some(Piece::new(PieceType::Knight, Player::Black)), sq("d5"));
+        let tile = board_state.get_tile_at_pos(sq("e5"));
 
-        assert!(MoveGeneration::within_bounds(&dir, &tile));
-    } 
+        let moves = MoveGeneration::gen_moves_pawn(tile, &board_state);
+
+        assert_eq!(end_positions(&moves), vec![sq("e6")]);
+    }
 
     #[test]
-    fn within_bounds_x_close_small() {
-        let dir = Direction{ up: 0, right: 0 };
-        let pos = (0, 0);
-        let tile = Tile::new(None, pos);
+    fn no_moves_once_game_over() {
+        let mut board_state = BoardState::new();
+        board_state.resign(Player::Black);
+        let tile = board_state.get_tile_at_pos(sq("g1"));
 
-        assert!(MoveGeneration::within_bounds(&dir, &tile));
-    } 
+        assert!(MoveGeneration::gen_moves_tile(tile, &board_state).is_empty());
+    }
 
     #[test]
-    fn within_bounds_y_close_big() {
-        let dir = Direction{ up: 0, right: 0 };
-        let pos = (0, 7);
-        let tile = Tile::new(None, pos);
+    fn gen_moves_for_player_starting_position() {
+        let board_state = BoardState::new();
 
-        assert!(MoveGeneration::within_bounds(&dir, &tile));
-    } 
+        let white_moves = MoveGeneration::gen_moves_for_player(&board_state, Player::White);
+        let black_moves = MoveGeneration::gen_moves_for_player(&board_state, Player::Black);
+
+        // sixteen pawn moves and four knight moves each
+        assert_eq!(white_moves.len(), 20);
+        assert_eq!(black_moves.len(), 20);
+        assert!(white_moves.iter().all(|stored_move| stored_move.start_pos.get_rank() <= 1));
+        assert!(black_moves.iter().all(|stored_move| stored_move.start_pos.get_rank() >= 6));
+    }
+
+    #[test]
+    fn gen_moves_for_player_includes_special_moves() {
+        let mut board_state = castle_board();
+        board_state.set_piece_at_pos(Some(Piece::new(PieceType::Pawn, Player::White)), sq("d7"));
+
+        let moves = MoveGeneration::gen_moves_for_player(&board_state, Player::White);
+
+        let castles = StoredMove { start_pos: sq("e1"), end_pos: sq("g1"), promotion: None };
+        let queen_castles = StoredMove { start_pos: sq("e1"), end_pos: sq("c1"), promotion: None };
+        assert!(moves.contains(&castles));
+        assert!(moves.contains(&queen_castles));
+        // a push and a capture of the king, each with four promotion choices
+        assert_eq!(moves.iter().filter(|stored_move| stored_move.promotion.is_some()).count(), 8);
+    }
 
     #[test]
-    fn within_bounds_y_close_small() {
-        let dir = Direction{ right: 0, up: -1 };
-        let pos = (0, 1);
-        let tile = Tile::new(None, pos);
+    fn gen_moves_for_player_en_passant() {
+        let mut board_state = BoardState::new();
+        board_state.set_piece_at_pos(Some(Piece::new(PieceType::Pawn, Player::Black)), sq("d4"));
+        board_state.move_piece(StoredMove { start_pos: sq("e2"), end_pos: sq("e4"), promotion: None });
 
-        assert!(MoveGeneration::within_bounds(&dir, &tile));
-    } 
+        let moves = MoveGeneration::gen_moves_for_player(&board_state, Player::Black);
+
+        assert!(moves.contains(&StoredMove { start_pos: sq("d4"), end_pos: sq("e3"), promotion: None }));
+    }
 
     #[test]
-    fn within_bounds_x_y_middle() {
-        let dir = Direction{ up: 1, right: 1 };
-        let pos = (4, 4);
-        let tile = Tile::new(None, pos);
+    fn gen_moves_for_player_game_over() {
+        let mut board_state = BoardState::new();
+        board_state.resign(Player::White);
+
+        assert!(MoveGeneration::gen_moves_for_player(&board_state, Player::Black).is_empty());
+    }
+
+    #[test]
+    fn gen_moves_for_pos_matches_tile() {
+        let board_state = BoardState::new();
+
+        let moves = MoveGeneration::gen_moves_for_pos(&board_state, sq("b1"));
+
+        assert_eq!(end_positions(&moves), vec![sq("c3"), sq("a3")]);
+    }
 
-        assert!(MoveGeneration::within_bounds(&dir, &tile));
-    } 
-}
\ No newline at end of file
+    #[test]
+    fn gen_moves_for_pos_empty_tile() {
+        let board_state = BoardState::new();
+
+        assert!(MoveGeneration::gen_moves_for_pos(&board_state, sq("e4")).is_empty());
+    }
+
+    fn lone_piece_moves(piece_type: PieceType, pos: Square) -> Vec<Square> {
+        let mut board_state = BoardState::empty();
+        board_state.set_piece_at_pos(Some(Piece::new(piece_type, Player::White)), pos);
+
+        end_positions(&MoveGeneration::gen_moves_for_pos(&board_state, pos))
+    }
+
+    fn no_duplicates(positions: &[Square]) -> bool {
+        positions.iter().enumerate().all(|(index, pos)| !positions[index + 1..].contains(pos))
+    }
+
+    fn diagonal_count(pos: Square) -> usize {
+        let (x, y) = (pos.get_file(), pos.get_rank());
+        x.min(y) + (7 - x).min(y) + x.min(7 - y) + (7 - x).min(7 - y)
+    }
+
+    #[test]
+    fn rook_empty_board_every_square() {
+        for pos in Square::all() {
+            let ends = lone_piece_moves(PieceType::Rook, pos);
+
+            assert_eq!(ends.len(), 14, "rook at {:?}", pos);
+            assert!(no_duplicates(&ends), "rook at {:?}", pos);
+            assert!(ends.iter().all(|end| (end.get_file() == pos.get_file()) != (end.get_rank() == pos.get_rank())), "rook at {:?}", pos);
+        }
+    }
+
+    #[test]
+    fn bishop_empty_board_every_square() {
+        for pos in Square::all() {
+            let ends = lone_piece_moves(PieceType::Bishop, pos);
+
+            assert_eq!(ends.len(), diagonal_count(pos), "bishop at {:?}", pos);
+            assert!(no_duplicates(&ends), "bishop at {:?}", pos);
+            assert!(ends.iter().all(|end| {
+                end != &pos && (end.get_file() as i32 - pos.get_file() as i32).abs() == (end.get_rank() as i32 - pos.get_rank() as i32).abs()
+            }), "bishop at {:?}", pos);
+        }
+    }
+
+    #[test]
+    fn queen_empty_board_every_square() {
+        for pos in Square::all() {
+            let ends = lone_piece_moves(PieceType::Queen, pos);
+            let mut expected = lone_piece_moves(PieceType::Rook, pos);
+            expected.append(&mut lone_piece_moves(PieceType::Bishop, pos));
+
+            assert_eq!(ends.len(), 14 + diagonal_count(pos), "queen at {:?}", pos);
+            assert!(no_duplicates(&ends), "queen at {:?}", pos);
+            assert!(ends.iter().all(|end| expected.contains(end)), "queen at {:?}", pos);
+        }
+    }
+
+    // surrounds a queen on every square with pieces, so every ray ends after one step
+    fn surrounded_queen_moves(pos: Square, neighbour_player: Player) -> Vec<Square> {
+        let mut board_state = BoardState::empty();
+        board_state.set_piece_at_pos(Some(Piece::new(PieceType::Queen, Player::White)), pos);
+        for neighbour in Square::all() {
+            let close = (neighbour.get_file() as i32 - pos.get_file() as i32).abs() <= 1
+                && (neighbour.get_rank() as i32 - pos.get_rank() as i32).abs() <= 1;
+            if close && neighbour != pos {
+                board_state.set_piece_at_pos(Some(Piece::new(PieceType::Pawn, neighbour_player)), neighbour);
+            }
+        }
+
+        end_positions(&MoveGeneration::gen_moves_for_pos(&board_state, pos))
+    }
+
+    #[test]
+    fn queen_stops_at_enemies_every_square() {
+        for pos in Square::all() {
+            let ends = surrounded_queen_moves(pos, Player::Black);
+
+            assert_eq!(ends, lone_piece_moves(PieceType::King, pos), "queen at {:?}", pos);
+        }
+    }
+
+    #[test]
+    fn queen_stops_before_friends_every_square() {
+        for pos in Square::all() {
+            assert!(surrounded_queen_moves(pos, Player::White).is_empty(), "queen at {:?}", pos);
+        }
+    }
+
+    #[test]
+    fn rook_ray_includes_enemy_and_stops() {
+        let mut board_state = BoardState::empty();
+        board_state.set_piece_at_pos(Some(Piece::new(PieceType::Rook, Player::White)), sq("a1"));
+        board_state.set_piece_at_pos(Some(Piece::new(PieceType::Pawn, Player::Black)), sq("a4"));
+        board_state.set_piece_at_pos(Some(Piece::new(PieceType::Pawn, Player::White)), sq("d1"));
+
+        let moves = MoveGeneration::gen_moves_for_pos(&board_state, sq("a1"));
+
+        assert_eq!(end_positions(&moves), vec![sq("a2"), sq("a3"), sq("a4"), sq("b1"), sq("c1")]);
+    }
+
+    #[test]
+    fn bishop_ray_steps_one_square_at_a_time() {
+        let mut board_state = BoardState::empty();
+        board_state.set_piece_at_pos(Some(Piece::new(PieceType::Bishop, Player::Black)), sq("a8"));
+        board_state.set_piece_at_pos(Some(Piece::new(PieceType::Knight, Player::White)), sq("f3"));
+
+        let moves = MoveGeneration::gen_moves_for_pos(&board_state, sq("a8"));
+
+        assert_eq!(end_positions(&moves), vec![sq("b7"), sq("c6"), sq("d5"), sq("e4"), sq("f3")]);
+    }
+
+    #[test]
+    fn sliding_pieces_blocked_in_starting_position() {
+        let board_state = BoardState::new();
+
+        for pos in [sq("a1"), sq("c1"), sq("d1"), sq("f1"), sq("h1"), sq("a8"), sq("d8"), sq("h8")] {
+            assert!(MoveGeneration::gen_moves_for_pos(&board_state, pos).is_empty(), "piece at {:?}", pos);
+        }
+    }
+}
@@ -0,0 +1,215 @@
+// Precomputed tables of the squares each piece attacks, as bitboards indexed by Square::index()
+// they are built once at compile time, so move generation only looks them up
+
+// this is a struct to make parsing all possible moves easier
+// up moves towards rank 8 and right moves towards the h file
+pub(super) struct Direction {
+    pub up: i32,
+    pub right: i32,
+}
+
+impl Direction {
+    // the index of the square one step from index, or None if the step leaves the board
+    pub(super) const fn step_from(&self, index: usize) -> Option<usize> {
+        let file = (index % 8) as i32 + self.right;
+        let rank = (index / 8) as i32 + self.up;
+
+        if file < 0 || file > 7 || rank < 0 || rank > 7 {
+            None
+        } else {
+            Some((rank * 8 + file) as usize)
+        }
+    }
+}
+
+// the directions kings and queens move in. the rook, bishop and pawn directions are indexes into these
+const DIRECTIONS: [Direction; 8] = [
+    Direction{ up: 1, right: 0 },
+    Direction{ up: 1, right: 1 },
+    Direction{ up: 0, right: 1 },
+    Direction{ up: -1, right: 1 },
+    Direction{ up: -1, right: 0 },
+    Direction{ up: -1, right: -1 },
+    Direction{ up: 0, right: -1 },
+    Direction{ up: 1, right: -1 },
+];
+
+pub(super) const UP: usize = 0;
+pub(super) const UP_RIGHT: usize = 1;
+pub(super) const RIGHT: usize = 2;
+pub(super) const DOWN_RIGHT: usize = 3;
+pub(super) const DOWN: usize = 4;
+pub(super) const DOWN_LEFT: usize = 5;
+pub(super) const LEFT: usize = 6;
+pub(super) const UP_LEFT: usize = 7;
+
+pub(super) const KING_QUEEN_DIRS: [usize; 8] = [UP, UP_RIGHT, RIGHT, DOWN_RIGHT, DOWN, DOWN_LEFT, LEFT, UP_LEFT];
+pub(super) const BISHOP_DIRS: [usize; 4] = [UP_RIGHT, DOWN_RIGHT, DOWN_LEFT, UP_LEFT];
+pub(super) const ROOK_DIRS: [usize; 4] = [UP, RIGHT, DOWN, LEFT];
+
+// the first pawn dir is the single step forward, the other two are the diagonal captures
+pub(super) const B_PAWN_DIRS: [usize; 3] = [DOWN, DOWN_RIGHT, DOWN_LEFT];
+pub(super) const W_PAWN_DIRS: [usize; 3] = [UP, UP_RIGHT, UP_LEFT];
+
+const KNIGHT_DIRS: [Direction; 8] = [
+    Direction{up: 2, right: 1},
+    Direction{up: 1, right: 2},
+    Direction{up: -1, right: 2},
+    Direction{up: -2, right: 1},
+    Direction{up: -2, right: -1},
+    Direction{up: -1, right: -2},
+    Direction{up: 1, right: -2},
+    Direction{up: 2, right: -1}
+];
+
+// the square one step in each of DIRECTIONS as a single bit, or 0 off the board. indexed [dir][square]
+pub(super) const STEPS: [[u64; 64]; 8] = step_tables(&DIRECTIONS);
+// the same for each of KNIGHT_DIRS
+pub(super) const KNIGHT_STEPS: [[u64; 64]; 8] = step_tables(&KNIGHT_DIRS);
+
+pub(super) const KING_ATTACKS: [u64; 64] = union_tables(&STEPS);
+pub(super) const KNIGHT_ATTACKS: [u64; 64] = union_tables(&KNIGHT_STEPS);
+
+// every square from one step in each of DIRECTIONS to the edge of the board. indexed [dir][square]
+const RAYS: [[u64; 64]; 8] = ray_tables();
+
+const fn step_tables(dirs: &[Direction; 8]) -> [[u64; 64]; 8] {
+    let mut tables = [[0; 64]; 8];
+
+    let mut dir = 0;
+    while dir < 8 {
+        let mut index = 0;
+        while index < 64 {
+            if let Some(end) = dirs[dir].step_from(index) {
+                tables[dir][index] = 1 << end;
+            }
+            index += 1;
+        }
+        dir += 1;
+    }
+    tables
+}
+
+const fn union_tables(tables: &[[u64; 64]; 8]) -> [u64; 64] {
+    let mut union = [0; 64];
+
+    let mut index = 0;
+    while index < 64 {
+        let mut dir = 0;
+        while dir < 8 {
+            union[index] |= tables[dir][index];
+            dir += 1;
+        }
+        index += 1;
+    }
+    union
+}
+
+const fn ray_tables() -> [[u64; 64]; 8] {
+    let mut tables = [[0; 64]; 8];
+
+    let mut dir = 0;
+    while dir < 8 {
+        let mut index = 0;
+        while index < 64 {
+            let mut step = DIRECTIONS[dir].step_from(index);
+            while let Some(end) = step {
+                tables[dir][index] |= 1 << end;
+                step = DIRECTIONS[dir].step_from(end);
+            }
+            index += 1;
+        }
+        dir += 1;
+    }
+    tables
+}
+
+// whether the square indexes grow along the dir, so the nearest square of a ray is its lowest bit
+pub(super) fn increasing(dir: usize) -> bool {
+    DIRECTIONS[dir].up > 0 || (DIRECTIONS[dir].up == 0 && DIRECTIONS[dir].right > 0)
+}
+
+// the squares a sliding piece on index reaches along dir, up to and including the first occupied square
+pub(super) fn sliding_attacks(dir: usize, index: usize, occupied: u64) -> u64 {
+    let ray = RAYS[dir][index];
+    let blockers = ray & occupied;
+    if blockers == 0 {
+        return ray;
+    }
+
+    let blocker = if increasing(dir) {
+        blockers.trailing_zeros()
+    } else {
+        63 - blockers.leading_zeros()
+    };
+    ray & !RAYS[dir][blocker as usize]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board_state::square::sq;
+    use crate::board_state::Bitboards;
+
+    fn bits(names: &[&str]) -> u64 {
+        names.iter().fold(0, |bits, name| bits | Bitboards::bit(sq(name)))
+    }
+
+    #[test]
+    fn step_from_off_each_edge() {
+        assert_eq!(DIRECTIONS[RIGHT].step_from(sq("h5").index()), None);
+        assert_eq!(DIRECTIONS[UP].step_from(sq("e8").index()), None);
+        assert_eq!(DIRECTIONS[LEFT].step_from(sq("a5").index()), None);
+        assert_eq!(DIRECTIONS[DOWN].step_from(sq("e1").index()), None);
+    }
+
+    #[test]
+    fn step_from_off_each_corner() {
+        assert_eq!(DIRECTIONS[UP_RIGHT].step_from(sq("h8").index()), None);
+        assert_eq!(DIRECTIONS[DOWN_RIGHT].step_from(sq("h1").index()), None);
+        assert_eq!(DIRECTIONS[DOWN_LEFT].step_from(sq("a1").index()), None);
+        assert_eq!(DIRECTIONS[UP_LEFT].step_from(sq("a8").index()), None);
+    }
+
+    #[test]
+    fn step_from_next_to_an_edge() {
+        assert_eq!(DIRECTIONS[RIGHT].step_from(sq("g5").index()), Some(sq("h5").index()));
+        assert_eq!(DIRECTIONS[DOWN].step_from(sq("a2").index()), Some(sq("a1").index()));
+        assert_eq!(KNIGHT_DIRS[0].step_from(sq("g6").index()), Some(sq("h8").index()));
+    }
+
+    #[test]
+    fn steps_off_the_board_are_empty() {
+        assert_eq!(STEPS[UP_RIGHT][sq("h8").index()], 0);
+        assert_eq!(STEPS[DOWN_LEFT][sq("a1").index()], 0);
+        assert_eq!(STEPS[UP][sq("e4").index()], Bitboards::bit(sq("e5")));
+    }
+
+    #[test]
+    fn knight_attacks_corner_and_centre() {
+        assert_eq!(KNIGHT_ATTACKS[sq("a1").index()], bits(&["b3", "c2"]));
+        assert_eq!(KNIGHT_ATTACKS[sq("d4").index()], bits(&["c2", "e2", "b3", "f3", "b5", "f5", "c6", "e6"]));
+    }
+
+    #[test]
+    fn king_attacks_edge() {
+        assert_eq!(KING_ATTACKS[sq("h4").index()], bits(&["g3", "h3", "g4", "g5", "h5"]));
+    }
+
+    #[test]
+    fn attack_counts_over_the_board() {
+        // the usual totals of king and knight moves from every square of an empty board
+        assert_eq!(KING_ATTACKS.iter().map(|bits| bits.count_ones()).sum::<u32>(), 420);
+        assert_eq!(KNIGHT_ATTACKS.iter().map(|bits| bits.count_ones()).sum::<u32>(), 336);
+    }
+
+    #[test]
+    fn sliding_attacks_stop_at_first_blocker() {
+        let occupied = bits(&["a4", "a6", "d1"]);
+
+        assert_eq!(sliding_attacks(UP, sq("a1").index(), occupied), bits(&["a2", "a3", "a4"]));
+        assert_eq!(sliding_attacks(RIGHT, sq("a1").index(), occupied), bits(&["b1", "c1", "d1"]));
+        assert_eq!(sliding_attacks(DOWN, sq("a8").index(), occupied), bits(&["a7", "a6"]));
+        assert_eq!(sliding_attacks(DOWN_LEFT, sq("c3").index(), occupied), bits(&["b2", "a1"]));
+    }
+}
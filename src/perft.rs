@@ -0,0 +1,124 @@
+use crate::board_state::{ BoardState, StoredMove };
+use crate::move_generation::MoveGeneration;
+
+// Perft counts the positions reachable in exactly depth moves, which checks move generation
+// against known counts. dark chess has no check, so every move from MoveGeneration counts
+// (moving into check, castling through attacked squares), and a game ends once a king is captured,
+// so a king capture is a leaf with no further moves
+
+pub fn perft(board_state: &BoardState, depth: usize) -> u64 {
+    perft_in_place(&mut board_state.clone(), depth)
+}
+
+// the perft count under each move from the position, in MoveGeneration order
+pub fn divide(board_state: &BoardState, depth: usize) -> Vec<(StoredMove, u64)> {
+    if depth == 0 {
+        return Vec::new();
+    }
+
+    let mut board_state = board_state.clone();
+    MoveGeneration::gen_moves_for_player(&board_state, *board_state.get_player_turn())
+        .into_iter()
+        .map(|stored_move| {
+            let undo_info = board_state.make_move(stored_move);
+            let nodes = perft_in_place(&mut board_state, depth - 1);
+            board_state.unmake_move(undo_info);
+
+            (stored_move, nodes)
+        })
+        .collect()
+}
+
+fn perft_in_place(board_state: &mut BoardState, depth: usize) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+
+    let moves = MoveGeneration::gen_moves_for_player(board_state, *board_state.get_player_turn());
+    if depth == 1 {
+        return moves.len() as u64;
+    }
+
+    let mut nodes = 0;
+    for stored_move in moves {
+        let undo_info = board_state.make_move(stored_move);
+        nodes += perft_in_place(board_state, depth - 1);
+        board_state.unmake_move(undo_info);
+    }
+
+    nodes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // reference counts for pseudo-legal moves with king capture, from an independent move generator.
+    // where these differ from the usual perft tables, it is because a side in check may still make
+    // any move, and castling ignores attacked squares
+    const START: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+    const KIWIPETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+    const ENDGAME: &str = "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1";
+    const PROMOTIONS: &str = "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1";
+    const DISCOVERIES: &str = "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8";
+
+    fn assert_counts(fen: &str, counts: &[u64]) {
+        let board_state = BoardState::from_fen(fen).unwrap();
+
+        for (depth, count) in counts.iter().enumerate() {
+            assert_eq!(perft(&board_state, depth + 1), *count, "depth {} of {}", depth + 1, fen);
+        }
+    }
+
+    #[test]
+    fn depth_zero_is_one_node() {
+        assert_eq!(perft(&BoardState::new(), 0), 1);
+    }
+
+    #[test]
+    fn starting_position() {
+        // the same as ordinary chess until depth 4, where checks start to matter
+        assert_counts(START, &[20, 400, 8902, 197742]);
+    }
+
+    #[test]
+    fn kiwipete() {
+        assert_counts(KIWIPETE, &[48, 2049, 98903]);
+    }
+
+    #[test]
+    fn rook_and_pawn_endgame() {
+        assert_counts(ENDGAME, &[16, 278, 4840, 88813]);
+    }
+
+    #[test]
+    fn promotions_while_in_check() {
+        assert_counts(PROMOTIONS, &[38, 1845, 71811]);
+    }
+
+    #[test]
+    fn discovered_attacks() {
+        assert_counts(DISCOVERIES, &[44, 1552, 71104]);
+    }
+
+    #[test]
+    fn king_capture_ends_the_line() {
+        // the rook can take the king, after which black has no moves at all
+        let board_state = BoardState::from_fen("4k3/8/8/8/8/8/8/4RK2 w - - 0 1").unwrap();
+        let capture = divide(&board_state, 2).into_iter()
+            .find(|(stored_move, _)| stored_move.to_uci() == "e1e8")
+            .unwrap();
+
+        assert_eq!(capture.1, 0);
+    }
+
+    #[test]
+    fn divide_adds_up_to_perft() {
+        let board_state = BoardState::from_fen(KIWIPETE).unwrap();
+
+        let divided = divide(&board_state, 2);
+
+        assert_eq!(divided.len(), 48);
+        assert_eq!(divided.iter().map(|(_, nodes)| nodes).sum::<u64>(), perft(&board_state, 2));
+    }
+}
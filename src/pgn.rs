@@ -0,0 +1,526 @@
+pub mod pgn_error;
+
+pub use crate::pgn::pgn_error::PgnError;
+
+use crate::board_state::{ BoardState, GameRules, GameStatus, Player, SanError, StoredMove };
+
+const VARIANT: &str = "Dark Chess";
+// the PGN export format keeps movetext lines under 80 characters
+const LINE_LENGTH: usize = 79;
+
+// A game in Portable Game Notation, as tag pairs and the moves played
+// the moves are always legal from the start position, which is the FEN tag if there is one
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pgn {
+    // written in this order, starting with the seven standard tags
+    tags: Vec<(String, String)>,
+    moves: Vec<StoredMove>,
+    // PGN has no tag for the rules, so they are only kept for replaying the moves
+    rules: GameRules,
+}
+
+impl Pgn {
+    // a game from the usual starting position, with the Result tag set from how the moves end it
+    pub fn from_moves(moves: &[StoredMove]) -> Result<Pgn, PgnError> {
+        Pgn::with_rules(None, moves, GameRules::new())
+    }
+
+    // a game from the position given in FEN, which is kept in the SetUp and FEN tags
+    pub fn from_position(fen: &str, moves: &[StoredMove]) -> Result<Pgn, PgnError> {
+        Pgn::with_rules(Some(fen), moves, GameRules::new())
+    }
+
+    // a game played under rules other than the default, from the usual starting position
+    // if fen is None. the moves are checked and replayed under these rules
+    pub fn with_rules(fen: Option<&str>, moves: &[StoredMove], rules: GameRules) -> Result<Pgn, PgnError> {
+        let mut tags: Vec<(String, String)> = [
+            ("Event", "?"),
+            ("Site", "?"),
+            ("Date", "????.??.??"),
+            ("Round", "?"),
+            ("White", "?"),
+            ("Black", "?"),
+            ("Result", "*"),
+            ("Variant", VARIANT),
+        ].iter().map(|(name, value)| (String::from(*name), String::from(*value))).collect();
+
+        if let Some(fen) = fen {
+            tags.push((String::from("SetUp"), String::from("1")));
+            tags.push((String::from("FEN"), String::from(fen)));
+        }
+
+        let mut pgn = Pgn { tags, moves: Vec::new(), rules };
+        let mut board_state = pgn.start_position()?;
+
+        for (index, stored_move) in moves.iter().enumerate() {
+            // a move from an empty tile has no SAN, so it is given as UCI instead
+            let san = match board_state.get_tile_at_pos(stored_move.start_pos).get_piece() {
+                Some(_) => stored_move.to_san(&board_state),
+                None => stored_move.to_uci(),
+            };
+            if board_state.try_move(*board_state.get_player_turn(), *stored_move).is_err() {
+                return Err(PgnError::IllegalMove { ply: index + 1, san });
+            }
+        }
+
+        pgn.moves = moves.to_vec();
+        pgn.set_result(board_state.get_game_status());
+
+        Ok(pgn)
+    }
+
+    // reads the tag pairs and replays the movetext, skipping move numbers, comments and NAGs
+    pub fn parse(text: &str) -> Result<Pgn, PgnError> {
+        Pgn::parse_with_rules(text, GameRules::new())
+    }
+
+    // as parse, for a game played under rules other than the default, since PGN cannot say which
+    pub fn parse_with_rules(text: &str, rules: GameRules) -> Result<Pgn, PgnError> {
+        let mut tags = Vec::new();
+        let mut movetext = String::new();
+
+        for line in text.lines() {
+            let trimmed = line.trim();
+            if trimmed.starts_with('[') {
+                tags.push(parse_tag(trimmed)?);
+            } else {
+                movetext.push_str(line);
+                movetext.push('\n');
+            }
+        }
+
+        let mut pgn = Pgn { tags, moves: Vec::new(), rules };
+        let mut board_state = pgn.start_position()?;
+
+        for (index, san) in movetext_sans(&movetext)?.into_iter().enumerate() {
+            let ply = index + 1;
+            let stored_move = match StoredMove::from_san(&san, &board_state) {
+                Ok(stored_move) => stored_move,
+                Err(SanError::Ambiguous(_)) => return Err(PgnError::AmbiguousMove { ply, san }),
+                Err(_) => return Err(PgnError::IllegalMove { ply, san }),
+            };
+
+            board_state.move_piece(stored_move);
+            pgn.moves.push(stored_move);
+        }
+
+        // a game ended by the moves themselves has to agree with its Result tag,
+        // anything else (such as a resignation) is taken from the tag as it is
+        let played = result_symbol(board_state.get_game_status());
+        match pgn.get_tag("Result") {
+            Some(tag) if board_state.get_game_status().is_over() && tag != played => {
+                return Err(PgnError::ResultMismatch { tag: String::from(tag), played: String::from(played) });
+            },
+            Some(_) => (),
+            None => pgn.set_tag("Result", played),
+        }
+
+        Ok(pgn)
+    }
+
+    pub fn get_tag(&self, name: &str) -> Option<&str> {
+        self.tags.iter()
+            .find(|(tag_name, _)| tag_name == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    // replaces the tag if it is already there, otherwise adds it at the end
+    pub fn set_tag(&mut self, name: &str, value: &str) {
+        match self.tags.iter_mut().find(|(tag_name, _)| tag_name == name) {
+            Some(tag) => tag.1 = String::from(value),
+            None => self.tags.push((String::from(name), String::from(value))),
+        }
+    }
+
+    // sets the Result tag, for games that end by something other than the moves, like a resignation
+    pub fn set_result(&mut self, game_status: &GameStatus) {
+        self.set_tag("Result", result_symbol(game_status));
+    }
+
+    pub fn get_moves(&self) -> &[StoredMove] {
+        &self.moves
+    }
+
+    // the position after every move has been played
+    pub fn replay(&self) -> Result<BoardState, PgnError> {
+        let mut board_state = self.start_position()?;
+        for stored_move in self.moves.iter() {
+            board_state.move_piece(*stored_move);
+        }
+
+        Ok(board_state)
+    }
+
+    // writes the game in PGN export format
+    // with visibility_comments, each move is followed by a comment with how many squares each side sees after it
+    pub fn write(&self, visibility_comments: bool) -> Result<String, PgnError> {
+        let mut result = String::new();
+        for (name, value) in self.tags.iter() {
+            let escaped = value.replace('\\', "\\\\").replace('"', "\\\"");
+            result.push_str(&format!("[{} \"{}\"]\n", name, escaped));
+        }
+        result.push('\n');
+
+        let mut tokens = Vec::new();
+        let mut board_state = self.start_position()?;
+
+        for (index, stored_move) in self.moves.iter().enumerate() {
+            let move_number = board_state.get_fullmove_number();
+            // black's moves only need their own number at the start, or after a comment
+            match board_state.get_player_turn() {
+                Player::White => tokens.push(format!("{}.", move_number)),
+                Player::Black if index == 0 || visibility_comments => tokens.push(format!("{}...", move_number)),
+                Player::Black => (),
+            }
+
+            tokens.push(stored_move.to_san(&board_state));
+            board_state.move_piece(*stored_move);
+
+            if visibility_comments {
+                tokens.push(format!(
+                    "{{white sees {}, black sees {}}}",
+                    visible_count(&board_state, Player::White),
+                    visible_count(&board_state, Player::Black)
+                ));
+            }
+        }
+        tokens.push(String::from(self.get_tag("Result").unwrap_or("*")));
+
+        result.push_str(&wrap_tokens(&tokens));
+        result.push('\n');
+
+        Ok(result)
+    }
+
+    fn start_position(&self) -> Result<BoardState, PgnError> {
+        match self.get_tag("FEN") {
+            Some(fen) => Ok(BoardState::from_fen_with_rules(fen, self.rules)?),
+            None => Ok(BoardState::with_rules(self.rules)),
+        }
+    }
+}
+
+fn result_symbol(game_status: &GameStatus) -> &'static str {
+    match game_status {
+        GameStatus::Ongoing => "*",
+        GameStatus::WhiteWins => "1-0",
+        GameStatus::BlackWins => "0-1",
+        GameStatus::Draw(_) => "1/2-1/2",
+    }
+}
+
+fn visible_count(board_state: &BoardState, player: Player) -> usize {
+    board_state.visible_tiles(player).iter().flatten().filter(|seen| **seen).count()
+}
+
+// a tag pair looks like [Name "value"], with \" and \\ escaped inside the value
+fn parse_tag(line: &str) -> Result<(String, String), PgnError> {
+    let malformed = || PgnError::MalformedTag(String::from(line));
+
+    let inner = line.strip_prefix('[').and_then(|rest| rest.strip_suffix(']')).ok_or_else(malformed)?;
+    let (name, value) = inner.split_once(char::is_whitespace).ok_or_else(malformed)?;
+    let value = value.trim().strip_prefix('"').and_then(|rest| rest.strip_suffix('"')).ok_or_else(malformed)?;
+
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return Err(malformed());
+    }
+
+    let mut unescaped = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => unescaped.push(chars.next().ok_or_else(malformed)?),
+            _ => unescaped.push(c),
+        }
+    }
+
+    Ok((String::from(name), unescaped))
+}
+
+// the moves in the movetext, without move numbers, comments, NAGs or the result
+fn movetext_sans(movetext: &str) -> Result<Vec<String>, PgnError> {
+    let mut tokens = Vec::new();
+    let mut token = String::new();
+    let mut chars = movetext.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' => {
+                if !chars.by_ref().any(|c| c == '}') {
+                    return Err(PgnError::UnterminatedComment);
+                }
+            },
+            ';' => {
+                chars.by_ref().find(|c| *c == '\n');
+            },
+            _ if c.is_whitespace() => (),
+            _ => {
+                token.push(c);
+                continue;
+            },
+        }
+
+        if !token.is_empty() {
+            tokens.push(std::mem::take(&mut token));
+        }
+    }
+    if !token.is_empty() {
+        tokens.push(token);
+    }
+
+    let mut sans = Vec::new();
+    for token in tokens {
+        // move numbers can be written on their own ("12." or "12...") or against the move ("12.e4")
+        let token = match token.rfind('.') {
+            Some(index) if token.starts_with(|c: char| c.is_ascii_digit()) => &token[index + 1..],
+            _ => &token[..],
+        };
+
+        if token.is_empty() || token.starts_with('$') || ["1-0", "0-1", "1/2-1/2", "*"].contains(&token) {
+            continue;
+        }
+        sans.push(String::from(token));
+    }
+
+    Ok(sans)
+}
+
+fn wrap_tokens(tokens: &[String]) -> String {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+
+    for token in tokens {
+        if !line.is_empty() && line.len() + 1 + token.len() > LINE_LENGTH {
+            lines.push(std::mem::take(&mut line));
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(token);
+    }
+    lines.push(line);
+
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board_state::square::sq;
+    use crate::board_state::{ Piece, PieceType };
+
+    fn mv(start: &str, end: &str) -> StoredMove {
+        StoredMove { start_pos: sq(start), end_pos: sq(end), promotion: None }
+    }
+
+    // white takes the black king on move 5, after black ignores the queen on f7
+    fn king_capture_moves() -> Vec<StoredMove> {
+        vec![
+            mv("e2", "e4"), mv("e7", "e5"),
+            mv("d1", "h5"), mv("b8", "c6"),
+            mv("f1", "c4"), mv("g8", "f6"),
+            mv("h5", "f7"), mv("a7", "a6"),
+            mv("f7", "e8"),
+        ]
+    }
+
+    const KING_CAPTURE_PGN: &str = "\
+[Event \"?\"]
+[Site \"?\"]
+[Date \"????.??.??\"]
+[Round \"?\"]
+[White \"?\"]
+[Black \"?\"]
+[Result \"1-0\"]
+[Variant \"Dark Chess\"]
+
+1. e4 e5 2. Qh5 Nc6 3. Bc4 Nf6 4. Qxf7 a6 5. Qxe8 1-0
+";
+
+    #[test]
+    fn write_empty_game() {
+        let pgn = Pgn::from_moves(&[]).unwrap();
+        let written = pgn.write(false).unwrap();
+
+        assert!(written.contains("[Variant \"Dark Chess\"]"));
+        assert!(written.contains("[Result \"*\"]"));
+        assert!(written.ends_with("\n\n*\n"));
+    }
+
+    #[test]
+    fn write_king_capture_game() {
+        let pgn = Pgn::from_moves(&king_capture_moves()).unwrap();
+
+        assert_eq!(pgn.get_tag("Result"), Some("1-0"));
+        assert_eq!(pgn.write(false).unwrap(), KING_CAPTURE_PGN);
+    }
+
+    #[test]
+    fn parse_king_capture_game() {
+        let pgn = Pgn::parse(KING_CAPTURE_PGN).unwrap();
+
+        assert_eq!(pgn.get_moves(), &king_capture_moves()[..]);
+        assert_eq!(pgn.get_tag("Variant"), Some("Dark Chess"));
+        assert_eq!(pgn.replay().unwrap().get_game_status(), &GameStatus::WhiteWins);
+    }
+
+    #[test]
+    fn from_moves_rejects_illegal_move_with_ply() {
+        let moves = [mv("e2", "e4"), mv("e7", "e5"), mv("e1", "e3")];
+
+        assert_eq!(
+            Pgn::from_moves(&moves).unwrap_err(),
+            PgnError::IllegalMove { ply: 3, san: String::from("Ke3") }
+        );
+    }
+
+    #[test]
+    fn from_moves_rejects_move_from_empty_tile_as_uci() {
+        let moves = [mv("e2", "e4"), mv("e6", "e5")];
+
+        assert_eq!(
+            Pgn::from_moves(&moves).unwrap_err(),
+            PgnError::IllegalMove { ply: 2, san: String::from("e6e5") }
+        );
+    }
+
+    #[test]
+    fn parse_rejects_illegal_move_with_ply() {
+        let result = Pgn::parse("1. e4 e5 2. Nf3 Qh4 3. Qh6 *");
+
+        assert_eq!(result.unwrap_err(), PgnError::IllegalMove { ply: 5, san: String::from("Qh6") });
+    }
+
+    #[test]
+    fn parse_rejects_moves_after_king_capture() {
+        let result = Pgn::parse("1. e4 e5 2. Qh5 Nc6 3. Bc4 Nf6 4. Qxf7 a6 5. Qxe8 a5 1-0");
+
+        assert_eq!(result.unwrap_err(), PgnError::IllegalMove { ply: 10, san: String::from("a5") });
+    }
+
+    #[test]
+    fn parse_rejects_wrong_result() {
+        let text = KING_CAPTURE_PGN.replace("1-0", "0-1");
+
+        assert_eq!(
+            Pgn::parse(&text).unwrap_err(),
+            PgnError::ResultMismatch { tag: String::from("0-1"), played: String::from("1-0") }
+        );
+    }
+
+    #[test]
+    fn parse_skips_comments_nags_and_move_numbers() {
+        let pgn = Pgn::parse("1.e4 {a comment} e5 $1 ; to the end of the line\n2. Qh5 2... Nc6 *").unwrap();
+
+        assert_eq!(pgn.get_moves(), &king_capture_moves()[..4]);
+        assert_eq!(pgn.get_tag("Result"), Some("*"));
+    }
+
+    #[test]
+    fn parse_unterminated_comment() {
+        assert_eq!(Pgn::parse("1. e4 {never closed").unwrap_err(), PgnError::UnterminatedComment);
+    }
+
+    #[test]
+    fn parse_malformed_tag() {
+        let result = Pgn::parse("[Event Casual]\n\n*");
+
+        assert_eq!(result.unwrap_err(), PgnError::MalformedTag(String::from("[Event Casual]")));
+    }
+
+    #[test]
+    fn tag_values_are_escaped() {
+        let mut pgn = Pgn::from_moves(&[]).unwrap();
+        pgn.set_tag("Event", "the \"big\" game \\ finals");
+
+        let written = pgn.write(false).unwrap();
+
+        assert!(written.contains("[Event \"the \\\"big\\\" game \\\\ finals\"]"));
+        assert_eq!(Pgn::parse(&written).unwrap().get_tag("Event"), Some("the \"big\" game \\ finals"));
+    }
+
+    #[test]
+    fn disambiguates_by_file_and_rejects_ambiguous() {
+        let fen = "4k3/8/8/8/8/8/8/1N2KN2 w - - 0 1";
+        let pgn = Pgn::from_position(fen, &[mv("b1", "d2")]).unwrap();
+
+        assert!(pgn.write(false).unwrap().ends_with("\n\n1. Nbd2 *\n"));
+
+        let ambiguous = format!("[SetUp \"1\"]\n[FEN \"{}\"]\n\n1. Nd2 *", fen);
+        assert_eq!(
+            Pgn::parse(&ambiguous).unwrap_err(),
+            PgnError::AmbiguousMove { ply: 1, san: String::from("Nd2") }
+        );
+    }
+
+    #[test]
+    fn disambiguates_by_rank() {
+        let fen = "4k3/8/8/8/R7/8/8/R3K3 w - - 0 1";
+        let pgn = Pgn::from_position(fen, &[mv("a1", "a2")]).unwrap();
+
+        assert!(pgn.write(false).unwrap().ends_with("\n\n1. R1a2 *\n"));
+    }
+
+    #[test]
+    fn castling_promotion_and_en_passant() {
+        let fen = "4k3/1P6/8/3pP3/8/8/8/4K2R w K d6 0 1";
+        let moves = [
+            mv("e5", "d6"), mv("e8", "d8"),
+            mv("e1", "g1"), mv("d8", "e8"),
+            StoredMove { start_pos: sq("b7"), end_pos: sq("b8"), promotion: Some(Piece::new(PieceType::Queen, Player::White)) },
+        ];
+        let pgn = Pgn::from_position(fen, &moves).unwrap();
+        let written = pgn.write(false).unwrap();
+
+        assert!(written.ends_with("\n\n1. exd6 Kd8 2. O-O Ke8 3. b8=Q *\n"));
+        assert_eq!(Pgn::parse(&written).unwrap().get_moves(), &moves[..]);
+    }
+
+    #[test]
+    fn starts_from_black_to_move() {
+        let pgn = Pgn::from_position("4k3/8/8/8/8/8/8/4K3 b - - 0 12", &[mv("e8", "d8")]).unwrap();
+
+        assert!(pgn.write(false).unwrap().ends_with("\n\n12... Kd8 *\n"));
+    }
+
+    #[test]
+    fn visibility_comments() {
+        let pgn = Pgn::from_moves(&king_capture_moves()[..2]).unwrap();
+        let mut board_state = BoardState::new();
+        board_state.move_piece(king_capture_moves()[0]);
+        let after_e4 = (visible_count(&board_state, Player::White), visible_count(&board_state, Player::Black));
+
+        let written = pgn.write(true).unwrap();
+
+        assert!(written.contains(&format!(
+            "1. e4 {{white sees {}, black sees {}}} 1... e5",
+            after_e4.0, after_e4.1
+        )));
+        assert_eq!(Pgn::parse(&written).unwrap().get_moves(), pgn.get_moves());
+    }
+
+    #[test]
+    fn long_games_wrap_under_eighty_columns() {
+        let shuffle = [mv("g1", "f3"), mv("g8", "f6"), mv("f3", "g1"), mv("f6", "g8")];
+        let moves: Vec<StoredMove> = shuffle.iter().chain(shuffle.iter()).copied().collect();
+
+        // the starting position comes up for the third time after the last move
+        let pgn = Pgn::from_moves(&moves).unwrap();
+        let written = pgn.write(true).unwrap();
+
+        assert_eq!(pgn.get_tag("Result"), Some("1/2-1/2"));
+        assert!(written.lines().all(|line| line.len() <= LINE_LENGTH));
+        assert_eq!(Pgn::parse(&written).unwrap().get_moves(), pgn.get_moves());
+    }
+
+    #[test]
+    fn set_result_for_resignation() {
+        let mut pgn = Pgn::from_moves(&king_capture_moves()[..2]).unwrap();
+        pgn.set_result(&GameStatus::BlackWins);
+
+        let written = pgn.write(false).unwrap();
+
+        assert!(written.ends_with("1. e4 e5 0-1\n"));
+        assert_eq!(Pgn::parse(&written).unwrap().get_tag("Result"), Some("0-1"));
+    }
+}
@@ -0,0 +1,61 @@
+use std::fmt;
+
+use crate::board_state::FenError;
+
+// The reasons a game cannot be turned into, or read from, PGN
+// plies count from 1, so ply 1 is white's first move
+#[derive(Debug, Clone, PartialEq)]
+pub enum PgnError {
+    MalformedTag(String),
+    UnterminatedComment,
+    InvalidFen(FenError),
+    // the move at this ply is not legal in the position it was played from, written as SAN
+    // unless there was no piece to move, when it is written as UCI
+    IllegalMove { ply: usize, san: String },
+    // more than one legal move matches the notation at this ply
+    AmbiguousMove { ply: usize, san: String },
+    // the Result tag disagrees with how the moves end the game
+    ResultMismatch { tag: String, played: String },
+}
+
+impl fmt::Display for PgnError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PgnError::MalformedTag(line) => write!(f, "malformed tag pair: {}", line),
+            PgnError::UnterminatedComment => write!(f, "a '{{' comment is never closed"),
+            PgnError::InvalidFen(error) => write!(f, "invalid FEN tag: {}", error),
+            PgnError::IllegalMove { ply, san } => write!(f, "illegal move '{}' at ply {}", san, ply),
+            PgnError::AmbiguousMove { ply, san } => write!(f, "ambiguous move '{}' at ply {}", san, ply),
+            PgnError::ResultMismatch { tag, played } => {
+                write!(f, "the Result tag is {} but the moves end the game as {}", tag, played)
+            },
+        }
+    }
+}
+
+impl std::error::Error for PgnError {}
+
+impl From<FenError> for PgnError {
+    fn from(error: FenError) -> PgnError {
+        PgnError::InvalidFen(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_illegal_move() {
+        let error = PgnError::IllegalMove { ply: 7, san: String::from("Qh5") };
+
+        assert_eq!(error.to_string(), "illegal move 'Qh5' at ply 7");
+    }
+
+    #[test]
+    fn display_invalid_fen() {
+        let error = PgnError::from(FenError::WrongRankCount(7));
+
+        assert_eq!(error.to_string(), "invalid FEN tag: expected 8 ranks in the piece placement, found 7");
+    }
+}
@@ -0,0 +1,101 @@
+// A small seeded random number generator (xorshift64*), so bots can be made reproducible
+// without pulling in a crate. it is fast and good enough for picking moves, not for anything secret
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    // the same seed always gives the same numbers. any seed is fine, including 0
+    pub fn new(seed: u64) -> Rng {
+        // xorshift gets stuck on a state of 0, so the seed is mixed first and never left at 0
+        let mut state = seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        state = (state ^ (state >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        state = (state ^ (state >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        state ^= state >> 31;
+
+        Rng { state: if state == 0 { 1 } else { state } }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    // a number from 0 up to but not including bound. panics if bound is 0
+    pub fn below(&mut self, bound: usize) -> usize {
+        assert!(bound > 0, "Rng::below needs a bound above 0");
+        (self.next_u64() % bound as u64) as usize
+    }
+
+    // a number from 0 up to but not including 1
+    pub fn next_f64(&mut self) -> f64 {
+        // the top 53 bits fill an f64 mantissa exactly
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    // a random element, or None if there are none to choose from
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> Option<&'a T> {
+        if items.is_empty() {
+            None
+        } else {
+            Some(&items[self.below(items.len())])
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_same_numbers() {
+        let mut first = Rng::new(42);
+        let mut second = Rng::new(42);
+
+        for _ in 0..100 {
+            assert_eq!(first.next_u64(), second.next_u64());
+        }
+    }
+
+    #[test]
+    fn different_seeds_differ() {
+        assert_ne!(Rng::new(1).next_u64(), Rng::new(2).next_u64());
+    }
+
+    #[test]
+    fn zero_seed_does_not_get_stuck() {
+        let mut rng = Rng::new(0);
+
+        assert_ne!(rng.next_u64(), rng.next_u64());
+    }
+
+    #[test]
+    fn below_stays_in_range_and_covers_it() {
+        let mut rng = Rng::new(7);
+        let mut seen = [false; 6];
+
+        for _ in 0..1000 {
+            let value = rng.below(6);
+            assert!(value < 6);
+            seen[value] = true;
+        }
+        assert!(seen.iter().all(|seen| *seen));
+    }
+
+    #[test]
+    fn next_f64_in_unit_range() {
+        let mut rng = Rng::new(3);
+
+        assert!((0..1000).map(|_| rng.next_f64()).all(|value| (0.0..1.0).contains(&value)));
+    }
+
+    #[test]
+    fn choose_from_empty() {
+        let empty: [u8; 0] = [];
+
+        assert_eq!(Rng::new(5).choose(&empty), None);
+    }
+}